@@ -115,6 +115,7 @@ pub fn build_app() -> App<'static, 'static> {
                 .alias("dereference")
                 .overrides_with("follow"),
         )
+        .arg(arg("prune").long("prune").overrides_with("prune"))
         .arg(
             arg("full-path")
                 .long("full-path")
@@ -135,6 +136,19 @@ pub fn build_app() -> App<'static, 'static> {
                 .hidden(true)
                 .takes_value(true),
         )
+        .arg(
+            arg("min-depth")
+                .long("min-depth")
+                .takes_value(true)
+                .value_name("depth"),
+        )
+        .arg(
+            arg("exact-depth")
+                .long("exact-depth")
+                .takes_value(true)
+                .value_name("depth")
+                .conflicts_with_all(&["depth", "rg-depth", "min-depth"]),
+        )
         .arg(
             arg("file-type")
                 .long("type")
@@ -150,6 +164,17 @@ pub fn build_app() -> App<'static, 'static> {
                     "directory",
                     "l",
                     "symlink",
+                    "broken-symlink",
+                    "s",
+                    "socket",
+                    "p",
+                    "pipe",
+                    "b",
+                    "block-device",
+                    "c",
+                    "char-device",
+                    "o",
+                    "other",
                     "x",
                     "executable",
                     "e",
@@ -229,12 +254,87 @@ pub fn build_app() -> App<'static, 'static> {
                 .allow_hyphen_values(true)
                 .multiple(true),
         )
+        .arg(
+            arg("json")
+                .long("json")
+                .overrides_with("json")
+                .conflicts_with_all(&["exec", "exec-batch", "null_separator"]),
+        )
+        .arg(
+            arg("list-details")
+                .long("list-details")
+                .short("l")
+                .overrides_with("list-details")
+                .conflicts_with_all(&["exec", "exec-batch", "json", "format"]),
+        )
+        .arg(
+            arg("show-link-target")
+                .long("show-link-target")
+                .overrides_with("show-link-target")
+                .conflicts_with_all(&["exec", "exec-batch", "json", "format"]),
+        )
+        .arg(
+            arg("format")
+                .long("format")
+                .takes_value(true)
+                .value_name("fmt")
+                .number_of_values(1)
+                .conflicts_with_all(&["exec", "exec-batch", "json"]),
+        )
+        .arg(
+            arg("max-results")
+                .long("max-results")
+                .takes_value(true)
+                .value_name("count")
+                .conflicts_with("max-one-result"),
+        )
+        .arg(
+            arg("max-one-result")
+                .short("1")
+                .hidden_short_help(true)
+                .overrides_with("max-one-result"),
+        )
+        .arg(
+            arg("sort")
+                .long("sort")
+                .takes_value(true)
+                .value_name("key")
+                .possible_values(&[
+                    "path",
+                    "name",
+                    "size",
+                    "mtime",
+                    "atime",
+                    "depth",
+                    "extension",
+                ])
+                .hide_possible_values(true)
+                .conflicts_with_all(&["exec", "exec-batch"]),
+        )
+        .arg(
+            arg("duplicates")
+                .long("duplicates")
+                .overrides_with("duplicates")
+                .conflicts_with_all(&["exec", "exec-batch", "list-details", "sort", "format"]),
+        )
+        .arg(
+            arg("reverse")
+                .long("reverse")
+                .overrides_with("reverse")
+                .conflicts_with_all(&["exec", "exec-batch"]),
+        )
         .arg(
             arg("max-buffer-time")
                 .long("max-buffer-time")
                 .takes_value(true)
                 .hidden(true),
         )
+        .arg(arg("dir-size").long("dir-size").overrides_with("dir-size"))
+        .arg(
+            arg("allocated-size")
+                .long("allocated-size")
+                .overrides_with("allocated-size"),
+        )
         .arg(
             arg("changed-within")
                 .long("changed-within")
@@ -251,6 +351,132 @@ pub fn build_app() -> App<'static, 'static> {
                 .value_name("date|dur")
                 .number_of_values(1),
         )
+        .arg(
+            arg("changed-between")
+                .long("changed-between")
+                .takes_value(true)
+                .value_name("start..end")
+                .number_of_values(1),
+        )
+        .arg(
+            arg("accessed-within")
+                .long("accessed-within")
+                .takes_value(true)
+                .value_name("date|dur")
+                .number_of_values(1),
+        )
+        .arg(
+            arg("accessed-before")
+                .long("accessed-before")
+                .takes_value(true)
+                .value_name("date|dur")
+                .number_of_values(1),
+        )
+        .arg(
+            arg("inode-changed-within")
+                .long("inode-changed-within")
+                .takes_value(true)
+                .value_name("date|dur")
+                .number_of_values(1),
+        )
+        .arg(
+            arg("inode-changed-before")
+                .long("inode-changed-before")
+                .takes_value(true)
+                .value_name("date|dur")
+                .number_of_values(1),
+        )
+        .arg(
+            arg("created-within")
+                .long("created-within")
+                .takes_value(true)
+                .value_name("date|dur")
+                .number_of_values(1),
+        )
+        .arg(
+            arg("created-before")
+                .long("created-before")
+                .takes_value(true)
+                .value_name("date|dur")
+                .number_of_values(1),
+        )
+        .arg(
+            arg("newer")
+                .long("newer")
+                .takes_value(true)
+                .value_name("path")
+                .number_of_values(1),
+        )
+        .arg(
+            arg("older")
+                .long("older")
+                .takes_value(true)
+                .value_name("path")
+                .number_of_values(1),
+        )
+        .arg(
+            arg("compare-time")
+                .long("compare-time")
+                .takes_value(true)
+                .value_name("timestamp")
+                .possible_values(&["mtime", "atime", "ctime", "btime"])
+                .hide_possible_values(true),
+        )
+        .arg(
+            arg("where")
+                .long("where")
+                .takes_value(true)
+                .value_name("expr")
+                .multiple(true)
+                .number_of_values(1),
+        )
+        .arg(
+            arg("contains")
+                .long("contains")
+                .takes_value(true)
+                .value_name("regex")
+                .multiple(true)
+                .number_of_values(1),
+        )
+        .arg(
+            arg("not-contains")
+                .long("not-contains")
+                .takes_value(true)
+                .value_name("regex")
+                .multiple(true)
+                .number_of_values(1),
+        )
+        .arg(
+            arg("mime")
+                .long("mime")
+                .takes_value(true)
+                .value_name("type")
+                .multiple(true)
+                .number_of_values(1),
+        )
+        .arg(
+            arg("link-target")
+                .long("link-target")
+                .takes_value(true)
+                .value_name("regex")
+                .multiple(true)
+                .number_of_values(1),
+        )
+        .arg(
+            arg("checksum")
+                .long("checksum")
+                .takes_value(true)
+                .value_name("algorithm")
+                .possible_values(&["sha256", "blake3", "md5"])
+                .hide_possible_values(true),
+        )
+        .arg(
+            arg("checksum-matches")
+                .long("checksum-matches")
+                .takes_value(true)
+                .value_name("hex")
+                .number_of_values(1),
+        )
         .arg(
             arg("show-errors")
                 .long("show-errors")
@@ -295,6 +521,21 @@ pub fn build_app() -> App<'static, 'static> {
                     .multiple(true)
                     .number_of_values(1),
             )
+            .arg(arg("nouser").long("nouser").overrides_with("nouser"))
+            .arg(arg("nogroup").long("nogroup").overrides_with("nogroup"))
+            .arg(arg("readable").long("readable").overrides_with("readable"))
+            .arg(arg("writable").long("writable").overrides_with("writable"))
+            .arg(
+                arg("executable-by-me")
+                    .long("executable-by-me")
+                    .overrides_with("executable-by-me"),
+            )
+            .arg(
+                arg("access-user")
+                    .long("access-user")
+                    .takes_value(true)
+                    .value_name("user"),
+            )
             .arg(
                 arg("permission")
                     .long("perm")
@@ -303,6 +544,40 @@ pub fn build_app() -> App<'static, 'static> {
                     .allow_hyphen_values(true)
                     .multiple(true)
                     .number_of_values(1),
+            )
+            .arg(
+                arg("links")
+                    .long("links")
+                    .takes_value(true)
+                    .value_name("[+-]num")
+                    .allow_hyphen_values(true)
+                    .multiple(true)
+                    .number_of_values(1),
+            )
+            .arg(arg("inum").long("inum").takes_value(true).value_name("num"))
+            .arg(
+                arg("samefile")
+                    .long("samefile")
+                    .takes_value(true)
+                    .value_name("path"),
+            )
+            .arg(
+                arg("unique-inode")
+                    .long("unique-inode")
+                    .overrides_with("unique-inode"),
+            )
+            .arg(
+                arg("xattr")
+                    .long("xattr")
+                    .takes_value(true)
+                    .value_name("name[=value]")
+                    .multiple(true)
+                    .number_of_values(1),
+            )
+            .arg(
+                arg("has-capabilities")
+                    .long("has-capabilities")
+                    .overrides_with("has-capabilities"),
             );
     }
 
@@ -365,6 +640,11 @@ fn usage() -> HashMap<&'static str, Help> {
         , "Follow symbolic links"
         , "By default, fd does not descend into symlinked directories. Using this flag, symbolic \
            links are also traversed.");
+    doc!(h, "prune"
+        , "Do not traverse into matching directories"
+        , "Do not traverse into directories that match the search criteria. A matching directory \
+           is still shown, but none of its contents are. Useful to list directories like \
+           'node_modules' or 'target' without their children.");
     doc!(h, "full-path"
         , "Search full path (default: file-/dirname only)"
         , "By default, the search pattern is only matched against the filename (or directory \
@@ -377,16 +657,31 @@ fn usage() -> HashMap<&'static str, Help> {
         , "Set maximum search depth (default: none)"
         , "Limit the directory traversal to a given depth. By default, there is no limit \
            on the search depth.");
+    doc!(h, "min-depth"
+        , "Only show results starting at the given depth"
+        , "Only show search results starting at the given depth. Entries directly inside a \
+           search path have a depth of 1. See also: '--max-depth' and '--exact-depth'.");
+    doc!(h, "exact-depth"
+        , "Only show results at the exact given depth"
+        , "Only show search results at the exact given depth. This is an alias for \
+           '--min-depth <depth> --max-depth <depth>'.");
     doc!(h, "rg-depth"
         , "See --max-depth"
         , "See --max-depth");
     doc!(h, "file-type"
-        , "Filter by type: file (f), directory (d), symlink (l),\nexecutable (x), empty (e)"
+        , "Filter by type: file (f), directory (d), symlink (l),\nbroken-symlink, executable (x), \
+           empty (e),\nsocket (s), pipe (p), block-device (b),\nchar-device (c), other (o)"
         , "Filter the search by type (multiple allowable filetypes can be specified):\n  \
              'f' or 'file':         regular files\n  \
              'd' or 'directory':    directories\n  \
              'l' or 'symlink':      symbolic links\n  \
-             'x' or 'executable':   executables\n  \
+             'broken-symlink':      symbolic links whose target does not exist\n  \
+             's' or 'socket':       sockets\n  \
+             'p' or 'pipe':         named pipes (FIFOs)\n  \
+             'b' or 'block-device': block devices\n  \
+             'c' or 'char-device':  character devices\n  \
+             'o' or 'other':        any type but files, directories and symlinks\n  \
+             'x' or 'executable':   files that the current user may execute\n  \
              'e' or 'empty':        empty files or directories");
     doc!(h, "extension"
         , "Filter by file extension"
@@ -433,6 +728,86 @@ fn usage() -> HashMap<&'static str, Help> {
         , "Set number of threads to use for searching & executing"
         , "Set number of threads to use for searching & executing (default: number of available \
            CPU cores)");
+    doc!(h, "json"
+        , "Print results as JSON lines, including metadata"
+        , "Print each search result as a JSON object on its own line. Each object has the \
+           fields 'path', 'type', 'size', 'mode' (octal permission bits), 'uid', 'gid', 'mtime' \
+           (RFC 3339) and 'depth'. If a path is not valid UTF-8, it is given as 'path_base64' \
+           (its raw bytes in base64) instead of 'path'. Fields that can not be determined are \
+           null. The extended attributes that matched '--xattr' are added as 'xattrs', a list of \
+           objects with a 'name' and a 'value' (or 'value_base64'). Symbolic links also have \
+           their 'target' (or 'target_base64').");
+    doc!(h, "list-details"
+        , "Use a long listing format with file metadata"
+        , "Print the permissions, owner, group, human-readable size and modification time of \
+           each search result in aligned columns, like 'ls -l'. All results are collected \
+           before anything is printed, and they are sorted by path unless '--sort' is given. \
+           Symbolic links are followed by the target they point to, like with \
+           '--show-link-target'.");
+    doc!(h, "show-link-target"
+        , "Print the target of symlinks after their path"
+        , "Print the target of symbolic links after their path, in the form 'path -> target'.");
+    doc!(h, "format"
+        , "Print results in the given format"
+        , "Print each search result in the given format instead of just its path. The format \
+           can contain the placeholders of '--exec', metadata fields, and the escapes \\t, \\n \
+           and \\\\. Literal braces are written as {{ and }}.\n\
+           Placeholders:\n  \
+             '{}', '{path}':  path\n  \
+             '{/}':           basename\n  \
+             '{//}':          parent directory\n  \
+             '{.}':           path without file extension\n  \
+             '{/.}':          basename without file extension\n  \
+             '{size}':        size in bytes\n  \
+             '{mode}':        permission bits in octal\n  \
+             '{perm}':        permissions like 'ls -l', e.g. drwxr-xr-x\n  \
+             '{owner}', '{group}', '{uid}', '{gid}':  owning user and group\n  \
+             '{depth}':       depth below the search path\n  \
+             '{type}':        file type\n  \
+             '{checksum}':    checksum given by '--checksum'\n  \
+             '{mime}':        MIME type, like for '--mime'\n  \
+             '{target}':      target of a symbolic link\n  \
+             '{mtime}', '{atime}':  modification and access time\n  \
+             '{mtime:%Y-%m-%d}':    modification time in the given strftime format\n\
+           Example:\n    \
+             --format '{path}\\t{size}\\t{mtime:%Y-%m-%d}'");
+    doc!(h, "max-results"
+        , "Limit the number of search results"
+        , "Limit the number of search results to 'count' and quit immediately. In combination \
+           with '--exec' or '--exec-batch', this limits the number of entries that commands are \
+           run for. With '--sort', the first 'count' results in sorted order are shown.");
+    doc!(h, "max-one-result"
+        , "Limit search to a single result"
+        , "Limit the search to a single result and quit immediately. \
+           This is an alias for '--max-results=1'.");
+    doc!(h, "sort"
+        , "Sort results by: path, name, size, mtime, atime, depth, extension"
+        , "Sort the search results by the given key. All results are collected before anything \
+           is printed, so the output is always fully ordered. Entries with equal keys are \
+           ordered by their path.\n  \
+             'path':       full path (default for --reverse)\n  \
+             'name':       file or directory name\n  \
+             'size':       size in bytes\n  \
+             'mtime':      modification time\n  \
+             'atime':      access time\n  \
+             'depth':      depth below the search root\n  \
+             'extension':  file extension");
+    doc!(h, "reverse"
+        , "Reverse the sort order"
+        , "Reverse the order of the search results. Implies '--sort path' unless another sort \
+           key is given.");
+    doc!(h, "duplicates"
+        , "Show groups of files with identical contents"
+        , "Only show regular files whose contents are identical to those of another result, in \
+           groups separated by empty lines. Each group starts with a line that gives the size of \
+           the files and the number of bytes wasted on copies. With '--json', each group is \
+           printed as one line instead. Files are compared by size first, then by a hash of \
+           their first 4 KiB and only then by a hash of their whole contents. All filters apply \
+           as usual, and '--max-results' limits the number of groups. Empty files are not shown. \
+           A file that is found through several paths (hard links, overlapping search paths or \
+           symlinks with '--follow') is not a copy of itself and only shown with its first path.\n\
+           Examples:\n    \
+               fd --duplicates -e jpg --size +100k");
     doc!(h, "max-buffer-time"
         , "the time (in ms) to buffer, before streaming to the console"
         , "Amount of time in milliseconds to buffer, before streaming the search results to \
@@ -448,10 +823,13 @@ fn usage() -> HashMap<&'static str, Help> {
         , "Alias for '--no-ignore'. Can be repeated; '-uu' is an alias for '--no-ignore --hidden'.");
     doc!(h, "size"
         , "Limit results based on the size of files."
-        , "Limit results based on the size of files using the format <+-><NUM><UNIT>.\n   \
+        , "Limit results based on the size of files using the format <+-=><NUM><UNIT>, or a \
+           range <NUM><UNIT>..<NUM><UNIT> (both ends included). Sizes are apparent sizes \
+           unless '--allocated-size' is given.\n   \
             '+': file size must be greater than or equal to this\n   \
             '-': file size must be less than or equal to this\n   \
-            'NUM':  The numeric size (e.g. 500)\n   \
+            '=': file size must be exactly this\n   \
+            'NUM':  The numeric size (e.g. 500 or 1.5)\n   \
             'UNIT': The units for NUM. They are not case-sensitive.\n\
             Allowed unit values:\n   \
                 'b':  bytes\n   \
@@ -462,23 +840,177 @@ fn usage() -> HashMap<&'static str, Help> {
                 'ki': kibibytes\n   \
                 'mi': mebibytes\n   \
                 'gi': gibibytes\n   \
-                'ti': tebibytes");
+                'ti': tebibytes\n\
+            Examples:\n    \
+                --size +1.5G\n    \
+                --size =4ki\n    \
+                --size 10k..2M");
+    doc!(h, "dir-size"
+        , "Apply --size to the total size of directories"
+        , "Compute the total size of each directory, including everything below it (also \
+           hidden and ignored files, like 'du'). Size constraints then apply to directories as \
+           well, and the total is shown as the size of a directory in '--list-details', \
+           '--json' and '--format', and used by '--sort size'. Each directory is only read once, \
+           however many of its ancestors are shown. Like with 'du', files with several hard links \
+           are counted once, and '--one-file-system' leaves out directories on other file \
+           systems.\n\
+           Example:\n    \
+               fd --dir-size --type d --size +1G --sort size");
+    doc!(h, "allocated-size"
+        , "Use the allocated disk space as size"
+        , "Compare and show the space that is allocated on disk (the number of blocks times \
+           512) instead of the apparent size. This is smaller than the apparent size for sparse \
+           files like VM images, and larger for small files. Has no effect on Windows.");
     doc!(h, "changed-within"
         , "Filter by file modification time (newer than)"
         , "Filter results based on the file modification time. The argument can be provided \
-           as a specific point in time (YYYY-MM-DD HH:MM:SS) or as a duration (10h, 1d, 35min). \
+           as a duration (10h, 1d, 35min), as a day (today, yesterday, 'last monday') or as a \
+           date or point in time (2024-05, 2024-05-17, YYYY-MM-DD HH:MM:SS). Days are in the \
+           local time zone. Dates and points in time are in UTC, unless a time zone (+02:00, or \
+           'local' for the local time zone) is given. \
            '--change-newer-than' can be used as an alias.\n\
            Examples:\n    \
                --changed-within 2weeks\n    \
-               --change-newer-than '2018-10-27 10:00:00'");
+               --changed-within yesterday\n    \
+               --change-newer-than '2018-10-27 10:00:00+02:00'");
     doc!(h, "changed-before"
         , "Filter by file modification time (older than)"
         , "Filter results based on the file modification time. The argument can be provided \
-           as a specific point in time (YYYY-MM-DD HH:MM:SS) or as a duration (10h, 1d, 35min). \
+           as a duration, a day or a point in time, like for '--changed-within'. \
            '--change-older-than' can be used as an alias.\n\
            Examples:\n    \
                --changed-before '2018-10-27 10:00:00'\n    \
                --change-older-than 2weeks");
+    doc!(h, "changed-between"
+        , "Filter by file modification time (closed interval)"
+        , "Only show results whose modification time is between two points in time, given as \
+           'START..END'. Both ends can be given like for '--changed-within'. An END without a \
+           time includes that whole day (or month or year), so '2024-01-01..2024-01-31' covers \
+           all of January.\n\
+           Examples:\n    \
+               --changed-between 2024-01-01..2024-02-01\n    \
+               --changed-between '2weeks..last monday'");
+    doc!(h, "accessed-within"
+        , "Filter by file access time (newer than)"
+        , "Filter results based on the time of the last access. The argument is given \
+           like for '--changed-within'. Note that many file systems are mounted with 'relatime' \
+           or 'noatime', so access times may lag behind.");
+    doc!(h, "accessed-before"
+        , "Filter by file access time (older than)"
+        , "Filter results based on the time of the last access. The argument is given \
+           like for '--changed-within'.\n\
+           Examples:\n    \
+               --accessed-before 90d");
+    doc!(h, "inode-changed-within"
+        , "Filter by inode change time (newer than)"
+        , "Filter results based on the time of the last status change (ctime), i.e. of the \
+           contents, permissions, owner or links. The argument is given like for \
+           '--changed-within'. Not available on Windows.");
+    doc!(h, "inode-changed-before"
+        , "Filter by inode change time (older than)"
+        , "Filter results based on the time of the last status change (ctime). The argument is \
+           given like for '--changed-within'. Not available on Windows.");
+    doc!(h, "created-within"
+        , "Filter by file creation time (newer than)"
+        , "Filter results based on the creation (birth) time. The argument is given like for \
+           '--changed-within'. Files whose creation time is not available (for example on file \
+           systems that do not record it) are not shown.");
+    doc!(h, "created-before"
+        , "Filter by file creation time (older than)"
+        , "Filter results based on the creation (birth) time. The argument is given like for \
+           '--changed-within'. Files whose creation time is not available are not shown.");
+    doc!(h, "newer"
+        , "Filter by modification time of a reference file (newer than)"
+        , "Only show results that were modified after the file at the given path. The time of \
+           the reference file is read once, before the search starts. Use '--compare-time' to \
+           compare another timestamp instead.\n\
+           Examples:\n    \
+               --newer build/stamp");
+    doc!(h, "older"
+        , "Filter by modification time of a reference file (older than)"
+        , "Only show results that were modified before the file at the given path. Use \
+           '--compare-time' to compare another timestamp instead.");
+    doc!(h, "compare-time"
+        , "Timestamp to compare for '--newer' and '--older'"
+        , "The timestamp that '--newer' and '--older' compare, both of the results and of the \
+           reference file:\n  \
+             'mtime':  time of the last modification (default)\n  \
+             'atime':  time of the last access\n  \
+             'ctime':  time of the last inode change (Unix only)\n  \
+             'btime':  creation (birth) time");
+    doc!(h, "where"
+        , "Filter by a boolean combination of filters"
+        , "Only show search results for which the given expression holds. Filters are written \
+           as 'name OP value' and combined with 'and', 'or', 'not' (or '&&', '||', '!') and \
+           parentheses. 'and' binds tighter than 'or', and two filters without an operator in \
+           between are combined with 'and'. If the option is given multiple times, all of the \
+           expressions have to hold. Values that contain spaces can be quoted with ' or \".\n\
+           The expression is combined with the other filter options with 'and'. Multiple \
+           '--owner' or '--perm' options still match if any of them holds, so '--owner root \
+           --owner bin --where ext=log' shows the log files of either root or bin.\n\
+           Filters:\n    \
+               size>10M, size<=1k, ..  Compare the size of files (OP: =, !=, <, <=, >, >=)\n    \
+               type=f,d                One of the types of '--type'\n    \
+               ext=log                 File extension\n    \
+               changed-within=2weeks   Like '--changed-within'\n    \
+               changed-before=2018-10-27  Like '--changed-before'\n    \
+               changed-between=2024-01..2024-02  Like '--changed-between'\n    \
+               accessed-within=1d, ..  Like '--accessed-within' (also: accessed-before, \
+               inode-changed-within/-before, created-within/-before)\n    \
+               owner=root              Like '--owner' (Unix only)\n    \
+               perm=-100               Like '--perm' (Unix only)\n\
+           Every filter can also be negated with '!=' instead of '='.\n\
+           Examples:\n    \
+               --where '(size>10M or ext=log) and not owner=root'\n    \
+               --where 'type=f !(ext=rs || ext=toml)'");
+    doc!(h, "contains"
+        , "Only show files whose contents match a regex"
+        , "Only show regular files with a line that matches the given regular expression. Files \
+           are read line by line until the first match. Binary files (files with a NUL byte near \
+           the start or a line longer than 4 MiB) and files that can not be read are skipped. If \
+           the option is given multiple times, all of the expressions have to match.");
+    doc!(h, "not-contains"
+        , "Only show files whose contents do not match a regex"
+        , "Only show regular files without a line that matches the given regular expression. \
+           Like with '--contains', binary files and files that can not be read are skipped.");
+    doc!(h, "mime"
+        , "Filter by MIME type, e.g. 'image/*'"
+        , "Only show regular files whose MIME type matches the given pattern. The type is \
+           detected from the first bytes of each file, with a built-in table of signatures, and \
+           only for files that passed all other filters except '--contains'. Either part of the \
+           pattern can be '*'. If the option is given multiple times, one of the patterns has \
+           to match. Text files are 'text/plain', unless they are recognized as scripts \
+           ('text/x-shellscript', 'text/x-script.python', ..), HTML or XML. The detected type \
+           can be printed with '--format {mime}'.\n\
+           Examples:\n    \
+               --mime 'image/*'\n    \
+               --mime text/x-shellscript");
+    doc!(h, "link-target"
+        , "Filter symlinks by their target"
+        , "Only show symbolic links whose target matches the given regular expression. The \
+           target is matched as it is stored in the link, without resolving it. If the option \
+           is given multiple times, all of the expressions have to match.\n\
+           Example:\n    \
+               fd --type broken-symlink --link-target '^/opt/old-package/'");
+    doc!(h, "checksum"
+        , "Print a checksum in front of each path: sha256, blake3, md5"
+        , "Compute the checksum of each regular file and print it in front of the path, in the \
+           format of 'sha256sum'. The checksums are computed in parallel while searching. Entries \
+           that are not regular files (or can not be read) get a '-' instead. With '--json', the \
+           checksum is added as a field named after the algorithm, and '--format' can use it as \
+           '{checksum}'.\n  \
+             'sha256':  SHA-256\n  \
+             'blake3':  BLAKE3\n  \
+             'md5':     MD5\n\
+           Examples:\n    \
+               fd --checksum sha256 -t f . dist > SHA256SUMS");
+    doc!(h, "checksum-matches"
+        , "Only show files with the given checksum"
+        , "Only show regular files whose checksum is the given one, in hex digits. The algorithm \
+           is the one given by '--checksum', or otherwise guessed from the length: MD5 for 32 \
+           digits, SHA-256 for 64.\n\
+           Examples:\n    \
+               --checksum-matches 9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08");
     doc!(h, "show-errors"
         , "Enable display of filesystem errors"
         , "Enable the display of filesystem errors for situations such as insufficient permissions \
@@ -501,6 +1033,35 @@ fn usage() -> HashMap<&'static str, Help> {
             , "Filter files by their user and/or group.\n\
                Format: (user|uid):(group|gid). Either side is optional.\n\
                Precede either uid or gid with a ! to exclude files instead.");
+        doc!(h, "nouser"
+            , "Filter by owners without a user account"
+            , "Only show files whose owner (uid) has no entry in the user database, e.g. \
+               because the account was deleted. Each uid is only looked up once.");
+        doc!(h, "nogroup"
+            , "Filter by groups that do not exist"
+            , "Only show files whose group (gid) has no entry in the group database. Each gid is \
+               only looked up once.");
+        doc!(h, "readable"
+            , "Filter by being readable by the current user"
+            , "Only show entries that the current user may read. The kernel is asked with \
+               faccessat(2), so the owner, ACLs and the like are taken into account. Symlinks \
+               are checked by their target.");
+        doc!(h, "writable"
+            , "Filter by being writable by the current user"
+            , "Only show entries that the current user may write, like '--readable'. Files on \
+               read-only file systems are not writable.");
+        doc!(h, "executable-by-me"
+            , "Filter by being executable by the current user"
+            , "Only show entries that the current user may execute (or, for directories, \
+               search), like '--readable'. Unlike '--type executable', this includes \
+               directories.");
+        doc!(h, "access-user"
+            , "Check access for the given user instead"
+            , "Check '--readable', '--writable' and '--executable-by-me' for the given user (name \
+               or uid) instead of the current one. Only the owner, group and permission bits \
+               can be checked for another user, not ACLs or read-only file systems.\n\
+               Example:\n    \
+                   fd --writable --access-user www-data /etc");
         doc!(h, "permission"
             , "Filter by permissions"
             , "Filter files by their permission bits. Format: !?[=-/]?[\\.0-7]{3,4}\n    \
@@ -508,7 +1069,51 @@ fn usage() -> HashMap<&'static str, Help> {
                    -mode: Match if all of permission bits are set for file.\n    \
                    /mode: Match if any of permission bits are set for file.\n\
                The mode is given as 3 or 4 octal digits. And . can be used as wildcard placeholder. \
-               Precede with a ! to exclude files instead.");
+               Precede with a ! to exclude files instead.\n\
+               The mode can also be given as comma-separated clauses like chmod's, which all \
+               have to hold: [ugoa]*[+-=][rwxst]*. '+' requires the bits to be set, '-' requires \
+               them to be unset, and '=' gives the exact r, w and x bits of the classes. Without a \
+               class, a clause is for all of them, so '-w' means that nobody may write. The \
+               clauses 'setuid', 'setgid' and 'sticky' require the special bits. With '/', only \
+               '+' clauses can be used, and any of their bits has to be set.\n\
+               Examples:\n    \
+                   --perm u+x,o-w\n    \
+                   --perm g=rw\n    \
+                   --perm /a+w\n    \
+                   --perm setuid");
+        doc!(h, "links"
+            , "Filter by the number of hard links"
+            , "Filter files by their number of hard links. '+num' matches files with at least \
+               num links, '-num' files with at most num links, and 'num' files with exactly num \
+               links.\n\
+               Example:\n    \
+                   --links +2");
+        doc!(h, "inum"
+            , "Filter by inode number"
+            , "Only show the entries with the given inode number (on any file system).");
+        doc!(h, "samefile"
+            , "Filter by being a hard link to a file"
+            , "Only show the entries that refer to the same inode as the given path, i.e. the \
+               file itself and all hard links to it. Symlinks are followed for the given path.");
+        doc!(h, "unique-inode"
+            , "Show hard links to the same file only once"
+            , "If several hard links to the same file are found, only show the first one. Which \
+               one that is depends on the order of the traversal.");
+        doc!(h, "xattr"
+            , "Filter by extended attributes"
+            , "Only show entries with an extended attribute whose name matches the given glob \
+               pattern, and whose value matches the pattern after '=' (if given). A terminating \
+               NUL of the value is ignored. If the option is given multiple times, all of the \
+               filters have to match. The attributes are only read for entries that passed the \
+               other metadata filters, and the ones that matched are included in the output of \
+               '--json'.\n\
+               Examples:\n    \
+                   --xattr 'user.*'\n    \
+                   --xattr 'security.selinux=*:httpd_sys_content_t:*'");
+        doc!(h, "has-capabilities"
+            , "Only show files with capabilities"
+            , "Only show files with capabilities, i.e. a 'security.capability' extended \
+               attribute. Same as '--xattr security.capability'.");
     }
 
     #[cfg(any(unix, windows))]
@@ -0,0 +1,122 @@
+// Copyright (c) 2017 fd developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0>
+// or the MIT license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::hash::Hash;
+use std::io::{self, Read};
+use std::mem;
+use std::path::Path;
+use std::sync::Mutex;
+
+use crate::fshelper;
+use crate::walk::DirEntry;
+
+/// The number of bytes at the start of a file that are hashed to tell apart files of the same
+/// size before their whole contents are read.
+const PARTIAL_HASH_BYTES: u64 = 4 * 1024;
+
+/// The entries of one stage, by their key. An entry is only hashed for the next stage once a
+/// second entry with the same key shows up, so files with a unique size are never read at all.
+/// `None` marks a key whose entries have already been passed on.
+type Stage<K> = Mutex<HashMap<K, Option<DirEntry>>>;
+
+/// Groups the files found by a search by their contents.
+///
+/// Files are compared by size first, then by a hash of their first few kilobytes, and only then
+/// by a hash of their whole contents. The hashing is done by the walker threads that add the
+/// files, while the traversal goes on. Files that can not be read are skipped. A file that is
+/// found through several paths (hard links, overlapping search paths, or symlinks with
+/// `--follow`) is not a duplicate of itself, and only reported with the first of its paths.
+#[derive(Default)]
+pub struct Duplicates {
+    by_size: Stage<u64>,
+    by_partial_hash: Stage<(u64, blake3::Hash)>,
+    by_hash: Mutex<HashMap<(u64, blake3::Hash), Vec<DirEntry>>>,
+}
+
+impl Duplicates {
+    /// Add a regular file of the given size.
+    pub fn add(&self, entry: DirEntry, size: u64) {
+        for entry in advance(&self.by_size, size, entry) {
+            let partial_hash = match hash_file(entry.path(), Some(PARTIAL_HASH_BYTES)) {
+                Ok(hash) => hash,
+                Err(_) => continue,
+            };
+
+            for entry in advance(&self.by_partial_hash, (size, partial_hash), entry) {
+                // The partial hash already covers all of a small file.
+                let hash = if size <= PARTIAL_HASH_BYTES {
+                    partial_hash
+                } else {
+                    match hash_file(entry.path(), None) {
+                        Ok(hash) => hash,
+                        Err(_) => continue,
+                    }
+                };
+
+                self.by_hash
+                    .lock()
+                    .unwrap()
+                    .entry((size, hash))
+                    .or_default()
+                    .push(entry);
+            }
+        }
+    }
+
+    /// Take the groups of files with identical contents, once all files have been added. The
+    /// files of a group are sorted by path, and the groups by the path of their first file.
+    pub fn take_groups(&self) -> Vec<Vec<DirEntry>> {
+        let by_hash = mem::take(&mut *self.by_hash.lock().unwrap());
+        let mut groups: Vec<_> = by_hash
+            .into_values()
+            .map(|mut entries| {
+                entries.sort_by(|a, b| a.path().cmp(b.path()));
+                let mut ids = HashSet::new();
+                entries.retain(|entry| {
+                    let id = entry.metadata().and_then(fshelper::file_id);
+                    match id {
+                        Some(id) => ids.insert(id),
+                        None => true,
+                    }
+                });
+                entries
+            })
+            .filter(|entries| entries.len() > 1)
+            .collect();
+        groups.sort_by(|a, b| a[0].path().cmp(b[0].path()));
+        groups
+    }
+}
+
+/// Add an entry to a stage, and return the entries that have to be passed on to the next one.
+fn advance<K: Hash + Eq>(stage: &Stage<K>, key: K, entry: DirEntry) -> Vec<DirEntry> {
+    let mut stage = stage.lock().unwrap();
+    match stage.get_mut(&key) {
+        None => {
+            stage.insert(key, Some(entry));
+            vec![]
+        }
+        Some(first) => match first.take() {
+            Some(first) => vec![first, entry],
+            None => vec![entry],
+        },
+    }
+}
+
+/// Hash the contents of a file, or only the given number of bytes at its start.
+fn hash_file(path: &Path, limit: Option<u64>) -> io::Result<blake3::Hash> {
+    let mut file = File::open(path)?;
+    let mut hasher = blake3::Hasher::new();
+    match limit {
+        Some(limit) => io::copy(&mut file.take(limit), &mut hasher)?,
+        None => io::copy(&mut file, &mut hasher)?,
+    };
+    Ok(hasher.finalize())
+}
@@ -0,0 +1,61 @@
+// Copyright (c) 2017 fd developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0>
+// or the MIT license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use std::error;
+use std::fmt::{self, Display, Formatter};
+use std::path::PathBuf;
+
+/// Errors that prevent a search from being started.
+///
+/// Errors that occur while the file system is being traversed (insufficient permissions, broken
+/// symlinks, ..) do not abort the search. They are yielded by `SearchResults` instead.
+#[derive(Debug)]
+pub enum Error {
+    /// The search pattern is not a valid regular expression.
+    InvalidPattern(regex::Error),
+
+    /// The file extensions could not be turned into a regular expression.
+    InvalidExtension(regex::Error),
+
+    /// An exclude pattern is not a valid glob.
+    InvalidExcludePattern(String),
+
+    /// The exclude patterns could not be combined.
+    ExcludePatterns(ignore::Error),
+
+    /// A custom ignore file could not be read.
+    InvalidIgnoreFile(PathBuf, ignore::Error),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Error::InvalidPattern(err) | Error::InvalidExtension(err) => write!(f, "{}", err),
+            Error::InvalidExcludePattern(pattern) => {
+                write!(f, "Malformed exclude pattern '{}'", pattern)
+            }
+            Error::ExcludePatterns(_) => write!(f, "Mismatch in exclude patterns"),
+            Error::InvalidIgnoreFile(path, err) => write!(
+                f,
+                "Malformed pattern in custom ignore file '{}': {}.",
+                path.to_string_lossy(),
+                err
+            ),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Error::InvalidPattern(err) | Error::InvalidExtension(err) => Some(err),
+            Error::ExcludePatterns(err) | Error::InvalidIgnoreFile(_, err) => Some(err),
+            Error::InvalidExcludePattern(_) => None,
+        }
+    }
+}
@@ -75,7 +75,7 @@ mod path_tests {
     use super::*;
 
     fn correct(input: &str) -> String {
-        input.replace('/', &MAIN_SEPARATOR.to_string())
+        input.replace('/', std::path::MAIN_SEPARATOR_STR)
     }
 
     macro_rules! func_tests {
@@ -8,39 +8,38 @@
 
 use super::CommandTemplate;
 use crate::exit_codes::{merge_exitcodes, ExitCode};
-use crate::walk::WorkerResult;
+use fd_find::SearchResults;
 use std::path::PathBuf;
-use std::sync::mpsc::Receiver;
 use std::sync::{Arc, Mutex};
 
-/// An event loop that listens for inputs from the `rx` receiver. Each received input will
+/// An event loop that listens for inputs from the shared search results. Each received input will
 /// generate a command with the supplied command template. The generated command will then
-/// be executed, and this process will continue until the receiver's sender has closed.
+/// be executed, and this process will continue until the search has finished.
 pub fn job(
-    rx: Arc<Mutex<Receiver<WorkerResult>>>,
+    rx: Arc<Mutex<SearchResults>>,
     cmd: Arc<CommandTemplate>,
     out_perm: Arc<Mutex<()>>,
     show_filesystem_errors: bool,
 ) -> ExitCode {
     let mut results: Vec<ExitCode> = Vec::new();
     loop {
-        // Create a lock on the shared receiver for this thread.
-        let lock = rx.lock().unwrap();
+        // Create a lock on the shared results for this thread.
+        let mut lock = rx.lock().unwrap();
 
-        // Obtain the next result from the receiver, else if the channel
-        // has closed, exit from the loop
-        let value: PathBuf = match lock.recv() {
-            Ok(WorkerResult::Entry(val)) => val,
-            Ok(WorkerResult::Error(err)) => {
+        // Obtain the next result, else if the search
+        // has finished, exit from the loop
+        let value: PathBuf = match lock.next() {
+            Some(Ok(entry)) => entry.path().to_owned(),
+            Some(Err(err)) => {
                 if show_filesystem_errors {
                     print_error!("{}", err);
                 }
                 continue;
             }
-            Err(_) => break,
+            None => break,
         };
 
-        // Drop the lock so that other threads can read from the receiver.
+        // Drop the lock so that other threads can read from the results.
         drop(lock);
         // Generate a command, execute it and store its exit code.
         results.push(cmd.generate_and_execute(&value, Arc::clone(&out_perm)))
@@ -50,13 +49,13 @@ pub fn job(
 }
 
 pub fn batch(
-    rx: Receiver<WorkerResult>,
+    rx: SearchResults,
     cmd: &CommandTemplate,
     show_filesystem_errors: bool,
 ) -> ExitCode {
-    let paths = rx.iter().filter_map(|value| match value {
-        WorkerResult::Entry(val) => Some(val),
-        WorkerResult::Error(err) => {
+    let paths = rx.filter_map(|value| match value {
+        Ok(entry) => Some(entry.path().to_owned()),
+        Err(err) => {
             if show_filesystem_errors {
                 print_error!("{}", err);
             }
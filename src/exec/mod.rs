@@ -23,9 +23,9 @@ use regex::Regex;
 use crate::exit_codes::ExitCode;
 
 use self::command::execute_command;
-use self::input::{basename, dirname, remove_extension};
+pub use self::input::{basename, dirname, remove_extension};
 pub use self::job::{batch, job};
-use self::token::Token;
+pub use self::token::Token;
 
 /// Execution mode of the command
 #[derive(Debug, Clone, Copy, PartialEq)]
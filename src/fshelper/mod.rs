@@ -6,12 +6,19 @@
 // notice may not be copied, modified, or distributed except
 // according to those terms.
 
+#[cfg(unix)]
+use std::collections::HashMap;
 use std::env::current_dir;
 use std::fs;
 use std::io;
 #[cfg(unix)]
-use std::os::unix::fs::{MetadataExt, PermissionsExt};
+use std::os::unix::fs::MetadataExt;
 use std::path::{Path, PathBuf};
+#[cfg(unix)]
+use std::sync::Mutex;
+
+#[cfg(unix)]
+use lazy_static::lazy_static;
 
 use crate::walk;
 
@@ -45,14 +52,15 @@ pub fn is_dir(path: &Path) -> bool {
     path.is_dir() && (path.file_name().is_some() || path.canonicalize().is_ok())
 }
 
+/// The device and inode number, which identify a file across hard links and different paths.
 #[cfg(unix)]
-pub fn is_executable(md: &fs::Metadata) -> bool {
-    md.permissions().mode() & 0o111 != 0
+pub fn file_id(md: &fs::Metadata) -> Option<(u64, u64)> {
+    Some((md.dev(), md.ino()))
 }
 
 #[cfg(windows)]
-pub fn is_executable(_: &fs::Metadata) -> bool {
-    false
+pub fn file_id(_: &fs::Metadata) -> Option<(u64, u64)> {
+    None
 }
 
 #[cfg(unix)]
@@ -70,6 +78,112 @@ pub fn get_permission(md: &fs::Metadata) -> u32 {
     md.mode()
 }
 
+/// Names of users or groups by their id, or `None` for ids without an entry in the user or group
+/// database.
+#[cfg(unix)]
+type NameCache = Mutex<HashMap<u32, Option<String>>>;
+
+#[cfg(unix)]
+lazy_static! {
+    /// The user and group names are looked up at most once per id and process, since every lookup
+    /// may go through NSS (and thus LDAP or the like). The tables are shared by all walker threads.
+    static ref USER_NAMES: NameCache = Mutex::default();
+    static ref GROUP_NAMES: NameCache = Mutex::default();
+}
+
+#[cfg(unix)]
+fn with_cached_name<T>(
+    cache: &NameCache,
+    id: u32,
+    lookup: fn(u32) -> Option<String>,
+    f: impl FnOnce(Option<&String>) -> T,
+) -> T {
+    let mut names = cache.lock().unwrap();
+    f(names.entry(id).or_insert_with(|| lookup(id)).as_ref())
+}
+
+#[cfg(unix)]
+fn lookup_user_name(uid: u32) -> Option<String> {
+    users::get_user_by_uid(uid).map(|u| u.name().to_string_lossy().into_owned())
+}
+
+#[cfg(unix)]
+fn lookup_group_name(gid: u32) -> Option<String> {
+    users::get_group_by_gid(gid).map(|g| g.name().to_string_lossy().into_owned())
+}
+
+/// The name of the user with the given uid, or `None` if there is no such user.
+#[cfg(unix)]
+pub fn get_user_name(uid: u32) -> Option<String> {
+    with_cached_name(&USER_NAMES, uid, lookup_user_name, |name| name.cloned())
+}
+
+/// The name of the group with the given gid, or `None` if there is no such group.
+#[cfg(unix)]
+pub fn get_group_name(gid: u32) -> Option<String> {
+    with_cached_name(&GROUP_NAMES, gid, lookup_group_name, |name| name.cloned())
+}
+
+/// Whether there is a user with the given uid.
+#[cfg(unix)]
+pub fn user_exists(uid: u32) -> bool {
+    with_cached_name(&USER_NAMES, uid, lookup_user_name, |name| name.is_some())
+}
+
+/// Whether there is a group with the given gid.
+#[cfg(unix)]
+pub fn group_exists(gid: u32) -> bool {
+    with_cached_name(&GROUP_NAMES, gid, lookup_group_name, |name| name.is_some())
+}
+
+/// The type and permission bits in the format of `ls -l`, e.g. `drwxr-xr-x`.
+#[cfg(unix)]
+pub fn mode_string(md: &fs::Metadata) -> String {
+    use std::os::unix::fs::FileTypeExt;
+
+    let file_type = md.file_type();
+    let type_char = if file_type.is_dir() {
+        'd'
+    } else if file_type.is_symlink() {
+        'l'
+    } else if file_type.is_fifo() {
+        'p'
+    } else if file_type.is_socket() {
+        's'
+    } else if file_type.is_block_device() {
+        'b'
+    } else if file_type.is_char_device() {
+        'c'
+    } else {
+        '-'
+    };
+
+    let mode = md.mode();
+    let bit = |mask: u32, c: char| if mode & mask != 0 { c } else { '-' };
+    // The execute position also shows the setuid, setgid and sticky bits.
+    let exec = |mask: u32, special: u32, set: char| match (mode & mask != 0, mode & special != 0) {
+        (true, true) => set,
+        (false, true) => set.to_ascii_uppercase(),
+        (true, false) => 'x',
+        (false, false) => '-',
+    };
+
+    [
+        type_char,
+        bit(0o400, 'r'),
+        bit(0o200, 'w'),
+        exec(0o100, 0o4000, 's'),
+        bit(0o040, 'r'),
+        bit(0o020, 'w'),
+        exec(0o010, 0o2000, 's'),
+        bit(0o004, 'r'),
+        bit(0o002, 'w'),
+        exec(0o001, 0o1000, 't'),
+    ]
+    .iter()
+    .collect()
+}
+
 pub fn is_empty(entry: &walk::DirEntry) -> bool {
     if let Some(file_type) = entry.file_type() {
         if file_type.is_dir() {
@@ -0,0 +1,210 @@
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, Metadata};
+use std::path::{Path, PathBuf};
+use std::sync::{Condvar, Mutex};
+use std::thread::{self, ThreadId};
+
+use crate::fshelper;
+use crate::internal::filter::SizeKind;
+
+/// The total sizes of directories, shared by all threads of a search.
+///
+/// The total of a directory includes the directory itself and everything below it, including
+/// hidden and ignored files, like `du` does. Symlinks are not followed, and with
+/// `same_file_system`, directories on other file systems are left out.
+///
+/// Every directory is only read once, no matter from which thread, through which path or in which
+/// order its ancestors and descendants are asked for: a thread that needs a total that another
+/// thread is still computing waits for it. Like with `du`, a file with several hard links is only
+/// counted once in the whole search, in the total of the first directory that it is found in.
+pub struct DirSizes {
+    kind: SizeKind,
+    same_file_system: bool,
+    totals: Mutex<HashMap<DirKey, Total>>,
+    /// Signaled whenever a total is done.
+    done: Condvar,
+    /// The files with more than one hard link that have been counted.
+    linked: Mutex<HashSet<(u64, u64)>>,
+}
+
+/// A directory is identified by its device and inode number where they are available, so that
+/// it is only read once even if it is reached through several paths.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+enum DirKey {
+    Id(u64, u64),
+    Path(PathBuf),
+}
+
+enum Total {
+    /// The total is being computed by the given thread.
+    InProgress(ThreadId),
+    Done(u64),
+}
+
+impl DirSizes {
+    pub fn new(kind: SizeKind, same_file_system: bool) -> DirSizes {
+        DirSizes {
+            kind,
+            same_file_system,
+            totals: Mutex::new(HashMap::new()),
+            done: Condvar::new(),
+            linked: Mutex::new(HashSet::new()),
+        }
+    }
+
+    /// The total size of the directory at `path`. Entries that can not be read are skipped.
+    pub fn total(&self, path: &Path) -> u64 {
+        let metadata = fs::symlink_metadata(path).ok();
+        self.total_of(path, metadata.as_ref())
+    }
+
+    fn total_of(&self, path: &Path, metadata: Option<&Metadata>) -> u64 {
+        let key = match metadata.and_then(fshelper::file_id) {
+            Some((device, inode)) => DirKey::Id(device, inode),
+            None => DirKey::Path(path.to_owned()),
+        };
+
+        {
+            let current = thread::current().id();
+            let mut totals = self.totals.lock().unwrap();
+            loop {
+                match totals.get(&key) {
+                    None => break,
+                    Some(&Total::Done(total)) => return total,
+                    // The directory contains itself, e.g. through a bind mount.
+                    Some(&Total::InProgress(thread)) if thread == current => return 0,
+                    Some(&Total::InProgress(_)) => totals = self.done.wait(totals).unwrap(),
+                }
+            }
+            totals.insert(key.clone(), Total::InProgress(current));
+        }
+
+        let own_device = metadata.and_then(device);
+        let mut total = metadata.map_or(0, |md| self.kind.of(md));
+        if let Ok(entries) = fs::read_dir(path) {
+            for entry in entries.filter_map(Result::ok) {
+                let metadata = match entry.metadata() {
+                    Ok(metadata) => metadata,
+                    Err(_) => continue,
+                };
+                total += if !metadata.is_dir() {
+                    self.file_size(&metadata)
+                } else if !self.same_file_system || device(&metadata) == own_device {
+                    self.total_of(&entry.path(), Some(&metadata))
+                } else {
+                    0
+                };
+            }
+        }
+
+        self.totals.lock().unwrap().insert(key, Total::Done(total));
+        self.done.notify_all();
+        total
+    }
+
+    /// The size of a file, or `0` if it has several hard links and was already counted.
+    fn file_size(&self, metadata: &Metadata) -> u64 {
+        match hard_link_id(metadata) {
+            Some(id) if !self.linked.lock().unwrap().insert(id) => 0,
+            _ => self.kind.of(metadata),
+        }
+    }
+}
+
+fn device(metadata: &Metadata) -> Option<u64> {
+    fshelper::file_id(metadata).map(|(device, _)| device)
+}
+
+/// The id of a file with more than one hard link, or `None` for any other file.
+#[cfg(unix)]
+fn hard_link_id(metadata: &Metadata) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+
+    if metadata.nlink() > 1 {
+        fshelper::file_id(metadata)
+    } else {
+        None
+    }
+}
+
+#[cfg(not(unix))]
+fn hard_link_id(_: &Metadata) -> Option<(u64, u64)> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempdir::TempDir;
+
+    #[test]
+    fn total_apparent_size() {
+        let root = TempDir::new("fd-dir-size").unwrap();
+        let dir = root.path().join("dir");
+        fs::create_dir_all(dir.join("sub/subsub")).unwrap();
+        fs::write(dir.join("a"), vec![0; 1000]).unwrap();
+        fs::write(dir.join("sub/b"), vec![0; 300]).unwrap();
+        fs::write(dir.join("sub/subsub/c"), vec![0; 20]).unwrap();
+
+        let dir_len = |path: &Path| fs::symlink_metadata(path).unwrap().len();
+        let subsub = dir_len(&dir.join("sub/subsub")) + 20;
+        let sub = dir_len(&dir.join("sub")) + 300 + subsub;
+
+        let dir_sizes = DirSizes::new(SizeKind::Apparent, false);
+        assert_eq!(dir_sizes.total(&dir.join("sub")), sub);
+        assert_eq!(dir_sizes.total(&dir), dir_len(&dir) + 1000 + sub);
+        assert_eq!(dir_sizes.total(&dir.join("sub/subsub")), subsub);
+
+        // Totals are remembered, even if the contents change.
+        fs::write(dir.join("sub/subsub/d"), vec![0; 5000]).unwrap();
+        assert_eq!(dir_sizes.total(&dir.join("sub/subsub")), subsub);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn count_hard_links_once() {
+        let root = TempDir::new("fd-dir-size").unwrap();
+        let dir = root.path().join("dir");
+        fs::create_dir_all(dir.join("one")).unwrap();
+        fs::create_dir_all(dir.join("two")).unwrap();
+        fs::write(dir.join("one/a"), vec![0; 1000]).unwrap();
+        fs::hard_link(dir.join("one/a"), dir.join("one/b")).unwrap();
+        fs::hard_link(dir.join("one/a"), dir.join("two/c")).unwrap();
+
+        let dir_len = |path: &Path| fs::symlink_metadata(path).unwrap().len();
+        let one = dir_len(&dir.join("one"));
+        let two = dir_len(&dir.join("two"));
+
+        // The file is counted with the first directory that it is found in.
+        let dir_sizes = DirSizes::new(SizeKind::Apparent, false);
+        assert_eq!(dir_sizes.total(&dir.join("one")), one + 1000);
+        assert_eq!(dir_sizes.total(&dir.join("two")), two);
+        assert_eq!(dir_sizes.total(&dir), dir_len(&dir) + one + two + 1000);
+
+        let dir_sizes = DirSizes::new(SizeKind::Apparent, false);
+        assert_eq!(dir_sizes.total(&dir), dir_len(&dir) + one + two + 1000);
+    }
+
+    #[test]
+    fn share_totals_between_threads_and_paths() {
+        let root = TempDir::new("fd-dir-size").unwrap();
+        let dir = root.path().join("dir");
+        for i in 0..20 {
+            fs::create_dir_all(dir.join(format!("sub{}/subsub", i))).unwrap();
+            fs::write(dir.join(format!("sub{}/subsub/f", i)), vec![0; 100]).unwrap();
+        }
+
+        let dir_sizes = DirSizes::new(SizeKind::Apparent, false);
+        let totals: Vec<u64> = thread::scope(|scope| {
+            let threads: Vec<_> = (0..8)
+                .map(|_| scope.spawn(|| dir_sizes.total(&dir)))
+                .collect();
+            threads.into_iter().map(|t| t.join().unwrap()).collect()
+        });
+        assert!(totals.iter().all(|&total| total == totals[0]));
+        assert!(totals[0] >= 20 * 100);
+
+        let sub = dir_sizes.total(&dir.join("sub3"));
+        assert_eq!(dir_sizes.total(&dir.join("sub3/subsub/..")), sub);
+    }
+}
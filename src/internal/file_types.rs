@@ -1,20 +1,137 @@
+use std::fs::{self, FileType};
+use std::io;
+use std::path::Path;
+
+use crate::fshelper;
+#[cfg(unix)]
+use crate::internal::filter::{Access, AccessFilter};
+use crate::walk::DirEntry;
+
 /// Whether or not to show
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
 pub struct FileTypes {
     pub files: bool,
     pub directories: bool,
     pub symlinks: bool,
+    /// Symlinks whose target does not exist (a subset of `symlinks`).
+    pub broken_symlinks: bool,
+    pub sockets: bool,
+    pub pipes: bool,
+    pub block_devices: bool,
+    pub char_devices: bool,
+    /// Any type other than files, directories and symlinks, including the special types above
+    /// and types that are not known to *fd*.
+    pub others: bool,
     pub executables_only: bool,
     pub empty_only: bool,
 }
 
-impl Default for FileTypes {
-    fn default() -> FileTypes {
-        FileTypes {
-            files: false,
-            directories: false,
-            symlinks: false,
-            executables_only: false,
-            empty_only: false,
+impl FileTypes {
+    /// Build the set of file types from names as accepted by `--type` (`f`, `file`, `d`,
+    /// `directory`, `l`, `symlink`, `broken-symlink`, `s`, `socket`, `p`, `pipe`, `b`,
+    /// `block-device`, `c`, `char-device`, `o`, `other`, `x`, `executable`, `e`, `empty`).
+    /// Returns `None` if one of the names is unknown.
+    pub fn from_names<'a, I: IntoIterator<Item = &'a str>>(names: I) -> Option<FileTypes> {
+        let mut file_types = FileTypes::default();
+        for name in names {
+            match name {
+                "f" | "file" => file_types.files = true,
+                "d" | "directory" => file_types.directories = true,
+                "l" | "symlink" => file_types.symlinks = true,
+                "broken-symlink" => file_types.broken_symlinks = true,
+                "s" | "socket" => file_types.sockets = true,
+                "p" | "pipe" => file_types.pipes = true,
+                "b" | "block-device" => file_types.block_devices = true,
+                "c" | "char-device" => file_types.char_devices = true,
+                "o" | "other" => file_types.others = true,
+                "x" | "executable" => {
+                    file_types.executables_only = true;
+                    file_types.files = true;
+                }
+                "e" | "empty" => {
+                    file_types.empty_only = true;
+                }
+                _ => return None,
+            }
+        }
+
+        // If only 'empty' was specified, search for both files and directories:
+        if file_types.empty_only && !(file_types.files || file_types.directories) {
+            file_types.files = true;
+            file_types.directories = true;
         }
+
+        Some(file_types)
     }
+
+    /// Whether the entry is of one of the selected types.
+    pub fn matches(&self, entry: &DirEntry) -> bool {
+        let entry_type = match entry.file_type() {
+            Some(entry_type) => entry_type,
+            None => return false,
+        };
+
+        let selected = if entry_type.is_file() {
+            self.files
+        } else if entry_type.is_dir() {
+            self.directories
+        } else if entry_type.is_symlink() {
+            // The type of an entry is only a symlink if it is not followed, or if its target does
+            // not exist, so this check only takes an extra system call without `--follow`.
+            self.symlinks || (self.broken_symlinks && is_broken_symlink(entry.path()))
+        } else {
+            self.others || self.matches_special(entry_type)
+        };
+
+        selected
+            && (!self.executables_only || is_executable(entry))
+            && (!self.empty_only || fshelper::is_empty(entry))
+    }
+
+    #[cfg(unix)]
+    fn matches_special(&self, file_type: FileType) -> bool {
+        use std::os::unix::fs::FileTypeExt;
+
+        (self.sockets && file_type.is_socket())
+            || (self.pipes && file_type.is_fifo())
+            || (self.block_devices && file_type.is_block_device())
+            || (self.char_devices && file_type.is_char_device())
+    }
+
+    #[cfg(not(unix))]
+    fn matches_special(&self, _: FileType) -> bool {
+        false
+    }
+}
+
+/// Whether the target of a symlink does not exist, or the symlink is part of a loop. A target that
+/// exists but cannot be looked at, e.g. without search permission on its directory, is not broken.
+fn is_broken_symlink(path: &Path) -> bool {
+    match fs::metadata(path) {
+        Ok(_) => false,
+        Err(err) => err.kind() == io::ErrorKind::NotFound || is_missing_or_loop(&err),
+    }
+}
+
+/// `ENOTDIR` (a path through a file) and `ELOOP` have no stable `ErrorKind` yet.
+#[cfg(all(unix, not(target_os = "redox")))]
+fn is_missing_or_loop(err: &io::Error) -> bool {
+    err.raw_os_error() == Some(libc::ENOTDIR) || err.raw_os_error() == Some(libc::ELOOP)
+}
+
+#[cfg(not(all(unix, not(target_os = "redox"))))]
+fn is_missing_or_loop(_: &io::Error) -> bool {
+    false
+}
+
+/// Whether the current user may execute the entry. The kernel is asked, so that the owner of the
+/// file, ACLs and `noexec` mounts are taken into account. There are no executable bits elsewhere.
+#[cfg(unix)]
+fn is_executable(entry: &DirEntry) -> bool {
+    AccessFilter::current_user(Access::Execute).matches(entry.path())
+}
+
+#[cfg(not(unix))]
+fn is_executable(_: &DirEntry) -> bool {
+    false
 }
@@ -0,0 +1,182 @@
+use std::fs::{self, Metadata};
+use std::os::unix::fs::{MetadataExt, PermissionsExt};
+use std::path::Path;
+
+/// A kind of access to a file.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Access {
+    Read,
+    Write,
+    Execute,
+}
+
+impl Access {
+    /// The permission bit of the owner for this access. The bits of the group and of others are
+    /// shifted by 3 and 6 bits.
+    fn owner_bit(self) -> u32 {
+        match self {
+            Access::Read => 0o400,
+            Access::Write => 0o200,
+            Access::Execute => 0o100,
+        }
+    }
+}
+
+/// A user whose access to files is checked, with the groups that they are a member of.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AccessUser {
+    uid: u32,
+    gids: Vec<u32>,
+}
+
+impl AccessUser {
+    /// Look up a user by name or uid. Returns `None` if there is no such user.
+    pub fn from_string(name_or_uid: &str) -> Option<AccessUser> {
+        let user = match name_or_uid.parse() {
+            Ok(uid) => users::get_user_by_uid(uid),
+            Err(_) => users::get_user_by_name(name_or_uid),
+        }?;
+
+        let mut gids = vec![user.primary_group_id()];
+        for group in user.groups().unwrap_or_default() {
+            if !gids.contains(&group.gid()) {
+                gids.push(group.gid());
+            }
+        }
+        Some(AccessUser {
+            uid: user.uid(),
+            gids,
+        })
+    }
+
+    /// Whether the user may access a file with the given metadata, judging by its owner, group
+    /// and permission bits alone. Like for the kernel, `root` may read and write anything, and
+    /// execute anything that has an execute bit set (or is a directory).
+    fn can_access(&self, access: Access, metadata: &Metadata) -> bool {
+        let mode = metadata.permissions().mode();
+        if self.uid == 0 {
+            return access != Access::Execute || metadata.is_dir() || mode & 0o111 != 0;
+        }
+
+        let bit = if self.uid == metadata.uid() {
+            access.owner_bit()
+        } else if self.gids.contains(&metadata.gid()) {
+            access.owner_bit() >> 3
+        } else {
+            access.owner_bit() >> 6
+        };
+        mode & bit != 0
+    }
+}
+
+/// Filter on whether a user may access an entry, e.g. `--writable`. Symlinks are followed, so a
+/// symlink is accessible if its target is.
+///
+/// For the current user, the kernel is asked with `faccessat`, so that ACLs, read-only file
+/// systems and the like are taken into account. For another user, only the owner, group and
+/// permission bits can be checked.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AccessFilter {
+    access: Access,
+    user: Option<AccessUser>,
+}
+
+impl AccessFilter {
+    /// Only include entries that the current user (the effective user and group ids of *fd*) may
+    /// access in the given way.
+    pub fn current_user(access: Access) -> AccessFilter {
+        AccessFilter { access, user: None }
+    }
+
+    /// Only include entries that the given user may access in the given way.
+    pub fn for_user(access: Access, user: AccessUser) -> AccessFilter {
+        AccessFilter {
+            access,
+            user: Some(user),
+        }
+    }
+
+    pub fn matches(&self, path: &Path) -> bool {
+        match self.user {
+            Some(ref user) => fs::metadata(path).is_ok_and(|m| user.can_access(self.access, &m)),
+            None => current_user_can_access(self.access, path),
+        }
+    }
+}
+
+#[cfg(not(target_os = "redox"))]
+fn current_user_can_access(access: Access, path: &Path) -> bool {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    let path = match CString::new(path.as_os_str().as_bytes()) {
+        Ok(path) => path,
+        Err(_) => return false,
+    };
+    let mode = match access {
+        Access::Read => libc::R_OK,
+        Access::Write => libc::W_OK,
+        Access::Execute => libc::X_OK,
+    };
+    // SAFETY: the path is a valid NUL-terminated string that outlives the call.
+    unsafe { libc::faccessat(libc::AT_FDCWD, path.as_ptr(), mode, libc::AT_EACCESS) == 0 }
+}
+
+#[cfg(target_os = "redox")]
+fn current_user_can_access(access: Access, path: &Path) -> bool {
+    let user = AccessUser::from_string(&users::get_effective_uid().to_string());
+    fs::metadata(path).is_ok_and(|m| user.is_some_and(|user| user.can_access(access, &m)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+    use tempdir::TempDir;
+
+    #[test]
+    fn access_by_permission_bits() {
+        let dir = TempDir::new("fd-access").unwrap();
+        let path = dir.path().join("file");
+        fs::write(&path, "").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o640)).unwrap();
+        let metadata = fs::metadata(&path).unwrap();
+
+        // A uid that is neither the owner's nor root's.
+        let stranger = 3_999_999;
+        let user = |uid, gids| AccessUser { uid, gids };
+
+        let owner = user(metadata.uid(), vec![]);
+        if metadata.uid() != 0 {
+            assert!(owner.can_access(Access::Read, &metadata));
+            assert!(owner.can_access(Access::Write, &metadata));
+            assert!(!owner.can_access(Access::Execute, &metadata));
+        }
+
+        let member = user(stranger, vec![metadata.gid()]);
+        let other = user(stranger, vec![metadata.gid().wrapping_add(1)]);
+        let root = user(0, vec![0]);
+        assert!(member.can_access(Access::Read, &metadata));
+        assert!(!member.can_access(Access::Write, &metadata));
+        assert!(!other.can_access(Access::Read, &metadata));
+
+        assert!(root.can_access(Access::Write, &metadata));
+        assert!(!root.can_access(Access::Execute, &metadata));
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o001)).unwrap();
+        assert!(root.can_access(Access::Execute, &fs::metadata(&path).unwrap()));
+    }
+
+    #[test]
+    fn access_of_current_user() {
+        let dir = TempDir::new("fd-access").unwrap();
+        let path = dir.path().join("file");
+        fs::write(&path, "").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).unwrap();
+
+        assert!(AccessFilter::current_user(Access::Read).matches(&path));
+        assert!(AccessFilter::current_user(Access::Write).matches(&path));
+        assert!(!AccessFilter::current_user(Access::Execute).matches(&path));
+        assert!(AccessFilter::current_user(Access::Execute).matches(dir.path()));
+        assert!(!AccessFilter::current_user(Access::Read).matches(&dir.path().join("missing")));
+    }
+}
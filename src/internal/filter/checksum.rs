@@ -0,0 +1,160 @@
+use std::fs::File;
+use std::io;
+use std::path::Path;
+
+use md5::Md5;
+use sha2::{Digest, Sha256};
+
+/// A hash algorithm for the checksums of files.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChecksumKind {
+    Sha256,
+    Blake3,
+    Md5,
+}
+
+impl ChecksumKind {
+    /// Parse the name of an algorithm: `sha256`, `blake3` or `md5`.
+    pub fn from_name(name: &str) -> Option<ChecksumKind> {
+        match name {
+            "sha256" => Some(ChecksumKind::Sha256),
+            "blake3" => Some(ChecksumKind::Blake3),
+            "md5" => Some(ChecksumKind::Md5),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ChecksumKind::Sha256 => "sha256",
+            ChecksumKind::Blake3 => "blake3",
+            ChecksumKind::Md5 => "md5",
+        }
+    }
+
+    /// The number of hex digits of a checksum.
+    pub fn hex_len(self) -> usize {
+        match self {
+            ChecksumKind::Sha256 | ChecksumKind::Blake3 => 64,
+            ChecksumKind::Md5 => 32,
+        }
+    }
+
+    /// The checksum of the contents of the file at `path`, in lowercase hex digits.
+    pub fn of_file(self, path: &Path) -> io::Result<String> {
+        let mut file = File::open(path)?;
+        let digest = match self {
+            ChecksumKind::Sha256 => {
+                let mut hasher = Sha256::new();
+                io::copy(&mut file, &mut hasher)?;
+                hasher.finalize().to_vec()
+            }
+            ChecksumKind::Blake3 => {
+                let mut hasher = blake3::Hasher::new();
+                io::copy(&mut file, &mut hasher)?;
+                hasher.finalize().as_bytes().to_vec()
+            }
+            ChecksumKind::Md5 => {
+                let mut hasher = Md5::new();
+                io::copy(&mut file, &mut hasher)?;
+                hasher.finalize().to_vec()
+            }
+        };
+        Ok(digest.iter().map(|b| format!("{:02x}", b)).collect())
+    }
+}
+
+/// Only include regular files with the given checksum, e.g. `--checksum-matches HEX`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChecksumFilter {
+    kind: ChecksumKind,
+    digest: String,
+}
+
+impl ChecksumFilter {
+    /// Match the checksum given in hex digits (in either case), computed with the given
+    /// algorithm.
+    pub fn new(kind: ChecksumKind, hex: &str) -> Option<ChecksumFilter> {
+        if hex.len() != kind.hex_len() || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return None;
+        }
+        Some(ChecksumFilter {
+            kind,
+            digest: hex.to_ascii_lowercase(),
+        })
+    }
+
+    /// Match the checksum given in hex digits, with the algorithm guessed from its length: MD5
+    /// for 32 digits, SHA-256 for 64.
+    pub fn guess(hex: &str) -> Option<ChecksumFilter> {
+        let kind = match hex.len() {
+            32 => ChecksumKind::Md5,
+            64 => ChecksumKind::Sha256,
+            _ => return None,
+        };
+        ChecksumFilter::new(kind, hex)
+    }
+
+    pub fn kind(&self) -> ChecksumKind {
+        self.kind
+    }
+
+    /// Whether a checksum (in lowercase hex digits, computed with `kind()`) matches.
+    pub fn matches(&self, digest: &str) -> bool {
+        self.digest == digest
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempdir::TempDir;
+
+    #[test]
+    fn checksums_of_file() {
+        let dir = TempDir::new("fd-checksum").unwrap();
+        let path = dir.path().join("abc");
+        fs::write(&path, "abc").unwrap();
+
+        assert_eq!(
+            ChecksumKind::Sha256.of_file(&path).unwrap(),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(
+            ChecksumKind::Blake3.of_file(&path).unwrap(),
+            "6437b3ac38465133ffb63b75273a8db548c558465d79db03fd359c6cd5bd9d85"
+        );
+        assert_eq!(
+            ChecksumKind::Md5.of_file(&path).unwrap(),
+            "900150983cd24fb0d6963f7d28e17f72"
+        );
+        assert!(ChecksumKind::Md5
+            .of_file(&dir.path().join("missing"))
+            .is_err());
+    }
+
+    #[test]
+    fn parse_filter() {
+        let md5 = "900150983CD24FB0D6963F7D28E17F72";
+        let filter = ChecksumFilter::guess(md5).unwrap();
+        assert_eq!(filter.kind(), ChecksumKind::Md5);
+        assert!(filter.matches("900150983cd24fb0d6963f7d28e17f72"));
+
+        let sha256 = "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad";
+        assert_eq!(
+            ChecksumFilter::guess(sha256).unwrap().kind(),
+            ChecksumKind::Sha256
+        );
+        assert_eq!(
+            ChecksumFilter::new(ChecksumKind::Blake3, sha256)
+                .unwrap()
+                .kind(),
+            ChecksumKind::Blake3
+        );
+
+        assert_eq!(ChecksumFilter::new(ChecksumKind::Blake3, md5), None);
+        assert_eq!(ChecksumFilter::guess("abc"), None);
+        assert_eq!(ChecksumFilter::guess(&"g".repeat(32)), None);
+    }
+}
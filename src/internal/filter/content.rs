@@ -0,0 +1,142 @@
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read};
+use std::path::Path;
+
+use regex::bytes::Regex;
+
+/// The number of bytes at the start of a file that are checked for NUL bytes to decide whether
+/// the file is binary.
+const BINARY_DETECTION_BYTES: usize = 8 * 1024;
+
+/// The maximum length of a line. Files with a longer line are treated as binary, so that a huge
+/// file without newlines is not read into memory at once.
+const MAX_LINE_BYTES: usize = 4 * 1024 * 1024;
+
+/// A filter on the contents of regular files, e.g. `--contains` or `--not-contains`.
+///
+/// The contents are matched line by line, and reading stops at the first matching line. Files
+/// that look binary (a NUL byte near the start, or a line longer than 4 MiB) and files that can
+/// not be read never pass the filter, regardless of whether it is negated. Entries that are not
+/// regular files never pass either.
+#[derive(Clone, Debug)]
+pub struct ContentFilter {
+    regex: Regex,
+    negated: bool,
+}
+
+impl ContentFilter {
+    /// Only include files with a line that matches `pattern`.
+    pub fn contains(pattern: &str) -> Result<ContentFilter, regex::Error> {
+        Ok(ContentFilter {
+            regex: Regex::new(pattern)?,
+            negated: false,
+        })
+    }
+
+    /// Only include files without a line that matches `pattern`.
+    pub fn not_contains(pattern: &str) -> Result<ContentFilter, regex::Error> {
+        Ok(ContentFilter {
+            regex: Regex::new(pattern)?,
+            negated: true,
+        })
+    }
+
+    pub fn matches(&self, path: &Path) -> bool {
+        match self.search(path) {
+            Some(found) => found != self.negated,
+            None => false,
+        }
+    }
+
+    /// Whether the file has a matching line, or `None` if it is binary or can not be read.
+    fn search(&self, path: &Path) -> Option<bool> {
+        let file = File::open(path).ok()?;
+        let mut reader = BufReader::with_capacity(64 * 1024, file);
+
+        let head = reader.fill_buf().ok()?;
+        let head = &head[..head.len().min(BINARY_DETECTION_BYTES)];
+        if head.contains(&0) {
+            return None;
+        }
+
+        let mut line = Vec::new();
+        loop {
+            line.clear();
+            let mut limited = reader.by_ref().take(MAX_LINE_BYTES as u64 + 1);
+            if limited.read_until(b'\n', &mut line).ok()? == 0 {
+                return Some(false);
+            }
+            if line.len() > MAX_LINE_BYTES {
+                return None;
+            }
+            if line.last() == Some(&b'\n') {
+                line.pop();
+            }
+            if self.regex.is_match(&line) {
+                return Some(true);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempdir::TempDir;
+
+    fn write_file(dir: &TempDir, name: &str, contents: &[u8]) -> std::path::PathBuf {
+        let path = dir.path().join(name);
+        fs::write(&path, contents).unwrap();
+        path
+    }
+
+    #[test]
+    fn match_lines() {
+        let dir = TempDir::new("fd-content").unwrap();
+        let text = write_file(&dir, "text", b"first line\nfn main() {}\nlast");
+
+        assert!(ContentFilter::contains("main").unwrap().matches(&text));
+        assert!(ContentFilter::contains("^last$").unwrap().matches(&text));
+        assert!(!ContentFilter::contains("line\nfn").unwrap().matches(&text));
+        assert!(!ContentFilter::not_contains("main").unwrap().matches(&text));
+        assert!(ContentFilter::not_contains("absent")
+            .unwrap()
+            .matches(&text));
+    }
+
+    #[test]
+    fn skip_binary_and_unreadable_files() {
+        let dir = TempDir::new("fd-content").unwrap();
+        let binary = write_file(&dir, "binary", b"main\0\x01\x02");
+
+        assert!(!ContentFilter::contains("main").unwrap().matches(&binary));
+        assert!(!ContentFilter::not_contains("absent")
+            .unwrap()
+            .matches(&binary));
+        assert!(!ContentFilter::not_contains("absent")
+            .unwrap()
+            .matches(dir.path()));
+        assert!(!ContentFilter::contains("")
+            .unwrap()
+            .matches(&dir.path().join("missing")));
+    }
+
+    #[test]
+    fn skip_files_with_long_lines() {
+        let dir = TempDir::new("fd-content").unwrap();
+        let mut contents = vec![b'a'; MAX_LINE_BYTES + 1];
+        contents.extend_from_slice(b"main");
+        let long = write_file(&dir, "long", &contents);
+
+        assert!(!ContentFilter::contains("main").unwrap().matches(&long));
+        assert!(!ContentFilter::not_contains("absent")
+            .unwrap()
+            .matches(&long));
+
+        let short = write_file(&dir, "short", &contents[MAX_LINE_BYTES - 3..]);
+        assert!(ContentFilter::contains("^aaaamain$")
+            .unwrap()
+            .matches(&short));
+    }
+}
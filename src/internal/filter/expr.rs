@@ -0,0 +1,507 @@
+use std::fs::Metadata;
+use std::iter::Peekable;
+use std::str::Chars;
+use std::time::SystemTime;
+
+#[cfg(unix)]
+use super::{OwnerFilter, PermissionFilter};
+use super::{SizeFilter, TimeFilter, Timestamp};
+#[cfg(unix)]
+use crate::fshelper;
+use crate::internal::FileTypes;
+use crate::walk::DirEntry;
+
+/// A boolean combination of filters, as given to `--where`.
+///
+/// The leaves are the filters that are also available as separate command-line options. They
+/// are combined with `and`, `or` and `not` (or `&&`, `||` and `!`) and can be grouped with
+/// parentheses, e.g. `(size>10M or ext=log) and not owner=root`. Two leaves that follow each
+/// other without an operator are combined with `and`.
+#[derive(Debug, PartialEq)]
+pub enum FilterExpr {
+    And(Box<FilterExpr>, Box<FilterExpr>),
+    Or(Box<FilterExpr>, Box<FilterExpr>),
+    Not(Box<FilterExpr>),
+    /// Holds for files whose size is within the limit.
+    Size(SizeFilter),
+    /// Holds for entries whose modification time is within the limit.
+    Time(TimeFilter),
+    /// Holds for entries of one of the given types.
+    Type(FileTypes),
+    /// Holds for entries with the given extension. Lowercase, without a leading dot.
+    Extension(String),
+    #[cfg(unix)]
+    Owner(OwnerFilter),
+    #[cfg(unix)]
+    Permission(PermissionFilter),
+}
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    LParen,
+    RParen,
+    And,
+    Or,
+    Not,
+    Predicate {
+        key: String,
+        op: &'static str,
+        value: String,
+    },
+}
+
+/// Comparison operators, longest first so that `>=` is not read as `>`.
+const OPERATORS: &[&str] = &[">=", "<=", "!=", "=", ">", "<"];
+
+impl FilterExpr {
+    /// Parse an expression. Relative times (`changed-within=2weeks`) are computed from
+    /// `ref_time`.
+    pub fn from_string(s: &str, ref_time: &SystemTime) -> Result<FilterExpr, String> {
+        let tokens = tokenize(s)?;
+        let mut parser = Parser {
+            tokens: tokens.into_iter().peekable(),
+            ref_time,
+        };
+
+        let expr = parser.parse_or()?;
+        match parser.tokens.next() {
+            None => Ok(expr),
+            Some(Token::RParen) => Err("unbalanced ')'".to_string()),
+            Some(token) => Err(format!("unexpected {:?}", token)),
+        }
+    }
+
+    /// Evaluate the expression for an entry. `metadata` is the (possibly unavailable) metadata
+    /// of the entry; it is only consulted by the filters that need it.
+    pub fn matches(&self, entry: &DirEntry, metadata: Option<&Metadata>) -> bool {
+        match self {
+            FilterExpr::And(lhs, rhs) => {
+                lhs.matches(entry, metadata) && rhs.matches(entry, metadata)
+            }
+            FilterExpr::Or(lhs, rhs) => {
+                lhs.matches(entry, metadata) || rhs.matches(entry, metadata)
+            }
+            FilterExpr::Not(expr) => !expr.matches(entry, metadata),
+            FilterExpr::Size(filter) => entry
+                .filtered_size()
+                .is_some_and(|size| filter.is_within(size)),
+            FilterExpr::Time(filter) => metadata.is_some_and(|md| filter.matches(md)),
+            FilterExpr::Type(file_types) => file_types.matches(entry),
+            FilterExpr::Extension(extension) => match entry.path().file_name() {
+                Some(name) => {
+                    let name = name.to_string_lossy().to_lowercase();
+                    name.len() > extension.len() + 1
+                        && name.ends_with(extension.as_str())
+                        && name[..name.len() - extension.len()].ends_with('.')
+                }
+                None => false,
+            },
+            #[cfg(unix)]
+            FilterExpr::Owner(filter) => metadata
+                .is_some_and(|md| filter.matches(fshelper::get_uid(md), fshelper::get_gid(md))),
+            #[cfg(unix)]
+            FilterExpr::Permission(filter) => {
+                metadata.is_some_and(|md| filter.matches(fshelper::get_permission(md)))
+            }
+        }
+    }
+
+    fn and(lhs: FilterExpr, rhs: FilterExpr) -> FilterExpr {
+        FilterExpr::And(Box::new(lhs), Box::new(rhs))
+    }
+
+    fn or(lhs: FilterExpr, rhs: FilterExpr) -> FilterExpr {
+        FilterExpr::Or(Box::new(lhs), Box::new(rhs))
+    }
+
+    fn not(expr: FilterExpr) -> FilterExpr {
+        FilterExpr::Not(Box::new(expr))
+    }
+
+    /// Build the leaf for `key op value`. `!=` is the negation of `=`.
+    fn from_predicate(
+        key: &str,
+        op: &str,
+        value: &str,
+        ref_time: &SystemTime,
+    ) -> Result<FilterExpr, String> {
+        if op == "!=" {
+            return FilterExpr::from_predicate(key, "=", value, ref_time).map(FilterExpr::not);
+        }
+
+        let invalid = |what: &str| format!("'{}' is not a valid {}", value, what);
+
+        if key == "size" {
+            if op == "=" {
+                // Ranges (`size=10k..2M`) are only allowed with `=`.
+                let filter = if value.contains("..") {
+                    SizeFilter::from_string(value)
+                } else {
+                    SizeFilter::from_string(&format!("={}", value))
+                };
+                return filter.map(FilterExpr::Size).ok_or_else(|| invalid("size"));
+            }
+
+            let min =
+                SizeFilter::from_string(&format!("+{}", value)).ok_or_else(|| invalid("size"))?;
+            let max =
+                SizeFilter::from_string(&format!("-{}", value)).ok_or_else(|| invalid("size"))?;
+            let (min, max) = (FilterExpr::Size(min), FilterExpr::Size(max));
+
+            // Strict comparisons are written so that, like all size filters, they only ever
+            // hold for files.
+            return Ok(match op {
+                ">=" => min,
+                "<=" => max,
+                ">" => FilterExpr::and(min, FilterExpr::not(max)),
+                _ => FilterExpr::and(max, FilterExpr::not(min)),
+            });
+        }
+
+        if op != "=" {
+            return Err(format!("'{}' can not be used with '{}'", op, key));
+        }
+
+        if key == "changed-between" {
+            return TimeFilter::between(ref_time, value)
+                .map(|(start, end)| FilterExpr::and(FilterExpr::Time(start), FilterExpr::Time(end)))
+                .ok_or_else(|| invalid("time interval"));
+        }
+
+        if let Some((timestamp, within)) = time_key(key) {
+            let filter = if within {
+                TimeFilter::after(ref_time, value)
+            } else {
+                TimeFilter::before(ref_time, value)
+            };
+            return filter
+                .map(|f| FilterExpr::Time(f.of(timestamp)))
+                .ok_or_else(|| invalid("time"));
+        }
+
+        match key {
+            "type" => FileTypes::from_names(value.split(','))
+                .map(FilterExpr::Type)
+                .ok_or_else(|| invalid("file type")),
+            "ext" | "extension" => {
+                let extension = value.trim_start_matches('.');
+                if extension.is_empty() {
+                    Err(invalid("extension"))
+                } else {
+                    Ok(FilterExpr::Extension(extension.to_lowercase()))
+                }
+            }
+            #[cfg(unix)]
+            "owner" => OwnerFilter::from_string(value)
+                .map(FilterExpr::Owner)
+                .ok_or_else(|| invalid("owner")),
+            #[cfg(unix)]
+            "perm" => PermissionFilter::from_string(value)
+                .map(FilterExpr::Permission)
+                .ok_or_else(|| invalid("permission")),
+            _ => Err(format!("unknown filter '{}'", key)),
+        }
+    }
+}
+
+/// The timestamp that a time filter key refers to, and whether it selects entries within the
+/// given time (as opposed to before it).
+fn time_key(key: &str) -> Option<(Timestamp, bool)> {
+    Some(match key {
+        "changed-within" | "change-newer-than" => (Timestamp::Modified, true),
+        "changed-before" | "change-older-than" => (Timestamp::Modified, false),
+        "accessed-within" => (Timestamp::Accessed, true),
+        "accessed-before" => (Timestamp::Accessed, false),
+        "inode-changed-within" => (Timestamp::InodeChanged, true),
+        "inode-changed-before" => (Timestamp::InodeChanged, false),
+        "created-within" => (Timestamp::Created, true),
+        "created-before" => (Timestamp::Created, false),
+        _ => return None,
+    })
+}
+
+struct Parser<'a, I: Iterator<Item = Token>> {
+    tokens: Peekable<I>,
+    ref_time: &'a SystemTime,
+}
+
+impl<'a, I: Iterator<Item = Token>> Parser<'a, I> {
+    fn parse_or(&mut self) -> Result<FilterExpr, String> {
+        let mut expr = self.parse_and()?;
+        while self.tokens.peek() == Some(&Token::Or) {
+            self.tokens.next();
+            expr = FilterExpr::or(expr, self.parse_and()?);
+        }
+        Ok(expr)
+    }
+
+    fn parse_and(&mut self) -> Result<FilterExpr, String> {
+        let mut expr = self.parse_unary()?;
+        loop {
+            match self.tokens.peek() {
+                Some(Token::And) => {
+                    self.tokens.next();
+                }
+                Some(Token::Not) | Some(Token::LParen) | Some(Token::Predicate { .. }) => {}
+                _ => return Ok(expr),
+            }
+            expr = FilterExpr::and(expr, self.parse_unary()?);
+        }
+    }
+
+    fn parse_unary(&mut self) -> Result<FilterExpr, String> {
+        match self.tokens.next() {
+            Some(Token::Not) => self.parse_unary().map(FilterExpr::not),
+            Some(Token::LParen) => {
+                let expr = self.parse_or()?;
+                match self.tokens.next() {
+                    Some(Token::RParen) => Ok(expr),
+                    _ => Err("missing ')'".to_string()),
+                }
+            }
+            Some(Token::Predicate { key, op, value }) => {
+                FilterExpr::from_predicate(&key, op, &value, self.ref_time)
+            }
+            Some(token) => Err(format!("unexpected {:?}", token)),
+            None => Err("unexpected end of expression".to_string()),
+        }
+    }
+}
+
+fn tokenize(s: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = vec![];
+    let mut chars = s.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '(' => {
+                chars.next();
+                tokens.push(Token::LParen);
+            }
+            ')' => {
+                chars.next();
+                tokens.push(Token::RParen);
+            }
+            '!' => {
+                chars.next();
+                tokens.push(Token::Not);
+            }
+            '&' | '|' => {
+                chars.next();
+                if chars.next() != Some(c) {
+                    return Err(format!("expected '{}{}'", c, c));
+                }
+                tokens.push(if c == '&' { Token::And } else { Token::Or });
+            }
+            _ => tokens.push(read_word(&mut chars)?),
+        }
+    }
+
+    Ok(tokens)
+}
+
+/// Read a keyword (`and`, `or`, `not`) or a `key op value` predicate.
+fn read_word(chars: &mut Peekable<Chars>) -> Result<Token, String> {
+    let mut key = String::new();
+    while let Some(&c) = chars.peek() {
+        if c.is_alphanumeric() || c == '-' || c == '_' {
+            key.push(c);
+            chars.next();
+        } else {
+            break;
+        }
+    }
+
+    let rest: String = chars.clone().collect();
+    let op = match OPERATORS.iter().find(|op| rest.starts_with(*op)) {
+        Some(op) => *op,
+        None => {
+            return match key.as_str() {
+                "and" => Ok(Token::And),
+                "or" => Ok(Token::Or),
+                "not" => Ok(Token::Not),
+                "" => Err(format!(
+                    "unexpected '{}'",
+                    rest.chars().next().unwrap_or(' ')
+                )),
+                _ => Err(format!("expected a comparison after '{}'", key)),
+            };
+        }
+    };
+    if key.is_empty() {
+        return Err(format!("missing filter name before '{}'", op));
+    }
+    for _ in 0..op.len() {
+        chars.next();
+    }
+
+    let mut value = String::new();
+    match chars.peek() {
+        Some(&quote) if quote == '\'' || quote == '"' => {
+            chars.next();
+            loop {
+                match chars.next() {
+                    Some(c) if c == quote => break,
+                    Some(c) => value.push(c),
+                    None => return Err(format!("unterminated quote in value of '{}'", key)),
+                }
+            }
+        }
+        _ => {
+            while let Some(&c) = chars.peek() {
+                if c.is_whitespace() || c == '(' || c == ')' {
+                    break;
+                }
+                value.push(c);
+                chars.next();
+            }
+        }
+    }
+    if value.is_empty() {
+        return Err(format!("missing value for '{}'", key));
+    }
+
+    Ok(Token::Predicate { key, op, value })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(s: &str) -> Result<FilterExpr, String> {
+        FilterExpr::from_string(s, &SystemTime::now())
+    }
+
+    fn ext(e: &str) -> FilterExpr {
+        FilterExpr::Extension(e.to_string())
+    }
+
+    fn size(s: &str) -> FilterExpr {
+        FilterExpr::Size(SizeFilter::from_string(s).unwrap())
+    }
+
+    #[test]
+    fn and_binds_tighter_than_or() {
+        assert_eq!(
+            parse("ext=a or ext=b and ext=c"),
+            Ok(FilterExpr::or(
+                ext("a"),
+                FilterExpr::and(ext("b"), ext("c"))
+            ))
+        );
+        assert_eq!(
+            parse("(ext=a || ext=b) && ext=c"),
+            Ok(FilterExpr::and(
+                FilterExpr::or(ext("a"), ext("b")),
+                ext("c")
+            ))
+        );
+    }
+
+    #[test]
+    fn implicit_and() {
+        assert_eq!(
+            parse("ext=a not ext=b"),
+            Ok(FilterExpr::and(ext("a"), FilterExpr::not(ext("b"))))
+        );
+    }
+
+    #[test]
+    fn negation() {
+        assert_eq!(parse("!ext=a"), Ok(FilterExpr::not(ext("a"))));
+        assert_eq!(
+            parse("not not ext=a"),
+            parse("ext=a").map(FilterExpr::not).map(FilterExpr::not)
+        );
+        assert_eq!(parse("ext!=.A"), Ok(FilterExpr::not(ext("a"))));
+    }
+
+    #[test]
+    fn size_comparisons() {
+        assert_eq!(parse("size>=10k"), Ok(size("+10k")));
+        assert_eq!(parse("size<=10k"), Ok(size("-10k")));
+        assert_eq!(
+            parse("size>10k"),
+            Ok(FilterExpr::and(size("+10k"), FilterExpr::not(size("-10k"))))
+        );
+        assert_eq!(parse("size=10k"), Ok(size("=10k")));
+        assert_eq!(parse("size=1.5M..2G"), Ok(size("1.5M..2G")));
+        assert_eq!(parse("size!=10k..2M"), Ok(FilterExpr::not(size("10k..2M"))));
+        assert!(parse("size>10k..2M").is_err());
+    }
+
+    #[test]
+    fn file_types() {
+        assert_eq!(
+            parse("type=f,d"),
+            Ok(FilterExpr::Type(
+                FileTypes::from_names(vec!["f", "d"]).unwrap()
+            ))
+        );
+    }
+
+    #[test]
+    fn quoted_values() {
+        let ref_time = SystemTime::now();
+        assert_eq!(
+            FilterExpr::from_string("changed-before='2018-10-27 10:00:00'", &ref_time),
+            Ok(FilterExpr::Time(
+                TimeFilter::before(&ref_time, "2018-10-27 10:00:00").unwrap()
+            ))
+        );
+    }
+
+    #[test]
+    fn time_intervals() {
+        let ref_time = SystemTime::now();
+        let (start, end) = TimeFilter::between(&ref_time, "2024-01..2024-02").unwrap();
+        assert_eq!(
+            FilterExpr::from_string("changed-between=2024-01..2024-02", &ref_time),
+            Ok(FilterExpr::and(
+                FilterExpr::Time(start),
+                FilterExpr::Time(end)
+            ))
+        );
+        assert!(FilterExpr::from_string("changed-between=2024-02..2024-01", &ref_time).is_err());
+    }
+
+    #[test]
+    fn timestamps() {
+        let ref_time = SystemTime::now();
+        assert_eq!(
+            FilterExpr::from_string("accessed-within=2d", &ref_time),
+            Ok(FilterExpr::Time(
+                TimeFilter::after(&ref_time, "2d")
+                    .unwrap()
+                    .of(Timestamp::Accessed)
+            ))
+        );
+        assert_eq!(
+            FilterExpr::from_string("created-before=1h", &ref_time),
+            Ok(FilterExpr::Time(
+                TimeFilter::before(&ref_time, "1h")
+                    .unwrap()
+                    .of(Timestamp::Created)
+            ))
+        );
+    }
+
+    #[test]
+    fn invalid_expressions() {
+        assert!(parse("").is_err());
+        assert!(parse("ext=a or").is_err());
+        assert!(parse("(ext=a").is_err());
+        assert!(parse("ext=a)").is_err());
+        assert!(parse("ext").is_err());
+        assert!(parse("ext=").is_err());
+        assert!(parse("ext>a").is_err());
+        assert!(parse("colour=red").is_err());
+        assert!(parse("size>10").is_err());
+        assert!(parse("type=q").is_err());
+        assert!(parse("ext=a & ext=b").is_err());
+        assert!(parse("changed-before='2018-10-27").is_err());
+    }
+}
@@ -0,0 +1,111 @@
+use std::fs::{self, Metadata};
+use std::io;
+use std::os::unix::fs::MetadataExt;
+use std::path::Path;
+
+/// Filter on the number of hard links.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LinksFilter {
+    Max(u64),
+    Min(u64),
+    Exact(u64),
+}
+
+impl LinksFilter {
+    /// Parse `+N` (at least N links), `-N` (at most N links) or `N` (exactly N links).
+    pub fn from_string(s: &str) -> Option<Self> {
+        let (constructor, count): (fn(u64) -> LinksFilter, &str) = match s.as_bytes().first()? {
+            b'+' => (LinksFilter::Min, &s[1..]),
+            b'-' => (LinksFilter::Max, &s[1..]),
+            _ => (LinksFilter::Exact, s),
+        };
+
+        if !count.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        count.parse().ok().map(constructor)
+    }
+
+    pub fn matches(&self, metadata: &Metadata) -> bool {
+        let links = metadata.nlink();
+        match *self {
+            LinksFilter::Max(limit) => links <= limit,
+            LinksFilter::Min(limit) => links >= limit,
+            LinksFilter::Exact(count) => links == count,
+        }
+    }
+}
+
+/// Filter on the identity of the inode.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum InodeFilter {
+    /// The inode has the given number, on any device.
+    Number(u64),
+    /// The inode is the one of a given file, i.e. the entry is a hard link to it (or the file
+    /// itself).
+    SameFile { dev: u64, ino: u64 },
+}
+
+impl InodeFilter {
+    /// Match the inode of the file at `path`. Symlinks are followed.
+    pub fn same_file(path: &Path) -> io::Result<Self> {
+        let metadata = fs::metadata(path)?;
+        Ok(InodeFilter::SameFile {
+            dev: metadata.dev(),
+            ino: metadata.ino(),
+        })
+    }
+
+    pub fn matches(&self, metadata: &Metadata) -> bool {
+        match *self {
+            InodeFilter::Number(ino) => metadata.ino() == ino,
+            InodeFilter::SameFile { dev, ino } => metadata.dev() == dev && metadata.ino() == ino,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempdir::TempDir;
+
+    #[test]
+    fn parse_links() {
+        assert_eq!(LinksFilter::from_string("+2"), Some(LinksFilter::Min(2)));
+        assert_eq!(LinksFilter::from_string("-1"), Some(LinksFilter::Max(1)));
+        assert_eq!(LinksFilter::from_string("3"), Some(LinksFilter::Exact(3)));
+
+        assert_eq!(LinksFilter::from_string(""), None);
+        assert_eq!(LinksFilter::from_string("+"), None);
+        assert_eq!(LinksFilter::from_string("+-2"), None);
+        assert_eq!(LinksFilter::from_string("2k"), None);
+    }
+
+    #[test]
+    fn match_links_and_inodes() {
+        let dir = TempDir::new("fd-inode").unwrap();
+        let file = dir.path().join("file");
+        let link = dir.path().join("link");
+        let other = dir.path().join("other");
+        fs::write(&file, "").unwrap();
+        fs::write(&other, "").unwrap();
+        fs::hard_link(&file, &link).unwrap();
+
+        let metadata = |path: &Path| fs::metadata(path).unwrap();
+
+        assert!(LinksFilter::Min(2).matches(&metadata(&link)));
+        assert!(!LinksFilter::Min(2).matches(&metadata(&other)));
+        assert!(LinksFilter::Exact(1).matches(&metadata(&other)));
+
+        let same_file = InodeFilter::same_file(&file).unwrap();
+        assert!(same_file.matches(&metadata(&file)));
+        assert!(same_file.matches(&metadata(&link)));
+        assert!(!same_file.matches(&metadata(&other)));
+
+        let number = InodeFilter::Number(metadata(&other).ino());
+        assert!(number.matches(&metadata(&other)));
+        assert!(!number.matches(&metadata(&file)));
+
+        assert!(InodeFilter::same_file(&dir.path().join("missing")).is_err());
+    }
+}
@@ -0,0 +1,45 @@
+use std::path::Path;
+
+use regex::bytes::Regex;
+
+use crate::internal::osstr_to_bytes;
+
+/// Filter on the target of symlinks, e.g. `--link-target '^/opt/old-package/'`.
+///
+/// The target is matched as it is stored in the link, without resolving it, so a relative target
+/// like `../lib/libfoo.so` is matched as such. Entries that are not symlinks never pass.
+#[derive(Clone, Debug)]
+pub struct LinkTargetFilter {
+    regex: Regex,
+}
+
+impl LinkTargetFilter {
+    pub fn new(pattern: &str) -> Result<LinkTargetFilter, regex::Error> {
+        Ok(LinkTargetFilter {
+            regex: Regex::new(pattern)?,
+        })
+    }
+
+    /// Whether a link target matches.
+    pub fn matches(&self, target: &Path) -> bool {
+        self.regex.is_match(&osstr_to_bytes(target.as_os_str()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn match_targets() {
+        let filter = LinkTargetFilter::new(r"^/opt/old-package/").unwrap();
+        assert!(filter.matches(Path::new("/opt/old-package/bin/tool")));
+        assert!(!filter.matches(Path::new("/opt/new-package/bin/tool")));
+        assert!(!filter.matches(Path::new("../opt/old-package/bin/tool")));
+
+        let relative = LinkTargetFilter::new(r"^\.\./").unwrap();
+        assert!(relative.matches(Path::new("../lib/libfoo.so")));
+
+        assert!(LinkTargetFilter::new("(").is_err());
+    }
+}
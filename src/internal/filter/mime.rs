@@ -0,0 +1,242 @@
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+/// The number of bytes at the start of a file that are used to detect its type.
+const HEADER_BYTES: u64 = 1024;
+
+/// Signatures of formats that may consist of text only: the offset of the magic bytes, the bytes
+/// themselves and the MIME type.
+const TEXT_SIGNATURES: &[(usize, &[u8], &str)] = &[
+    (0, b"%PDF-", "application/pdf"),
+    (0, b"%!PS", "application/postscript"),
+    (0, b"{\\rtf", "text/rtf"),
+];
+
+/// Signatures of binary formats, like `TEXT_SIGNATURES`. They are only checked for files that
+/// do not look like text, since some of them are short enough to start a line of text, too.
+const SIGNATURES: &[(usize, &[u8], &str)] = &[
+    (0, b"\x89PNG\r\n\x1a\n", "image/png"),
+    (0, b"\xff\xd8\xff", "image/jpeg"),
+    (0, b"GIF87a", "image/gif"),
+    (0, b"GIF89a", "image/gif"),
+    (8, b"WEBP", "image/webp"),
+    (0, b"II*\0", "image/tiff"),
+    (0, b"MM\0*", "image/tiff"),
+    (0, b"\0\0\x01\0", "image/vnd.microsoft.icon"),
+    (0, b"BM", "image/bmp"),
+    (0, b"PK\x03\x04", "application/zip"),
+    (0, b"\x1f\x8b", "application/gzip"),
+    (0, b"BZh", "application/x-bzip2"),
+    (0, b"\xfd7zXZ\0", "application/x-xz"),
+    (0, b"\x28\xb5\x2f\xfd", "application/zstd"),
+    (0, b"7z\xbc\xaf\x27\x1c", "application/x-7z-compressed"),
+    (257, b"ustar", "application/x-tar"),
+    (0, b"\x7fELF", "application/x-executable"),
+    (0, b"MZ", "application/x-dosexec"),
+    (0, b"\0asm", "application/wasm"),
+    (0, b"SQLite format 3\0", "application/vnd.sqlite3"),
+    (0, b"OggS", "audio/ogg"),
+    (0, b"fLaC", "audio/flac"),
+    (0, b"ID3", "audio/mpeg"),
+    (8, b"WAVE", "audio/x-wav"),
+    (8, b"AVI ", "video/x-msvideo"),
+    (4, b"ftyp", "video/mp4"),
+    (0, b"\x1a\x45\xdf\xa3", "video/x-matroska"),
+    (0, b"wOFF", "font/woff"),
+    (0, b"wOF2", "font/woff2"),
+    (0, b"OTTO", "font/otf"),
+];
+
+/// Script interpreters (as named in a `#!` line) and the MIME type of their scripts.
+const INTERPRETERS: &[(&str, &str)] = &[
+    ("sh", "text/x-shellscript"),
+    ("bash", "text/x-shellscript"),
+    ("dash", "text/x-shellscript"),
+    ("ksh", "text/x-shellscript"),
+    ("zsh", "text/x-shellscript"),
+    ("python", "text/x-script.python"),
+    ("perl", "text/x-perl"),
+    ("ruby", "text/x-ruby"),
+    ("node", "application/javascript"),
+];
+
+/// Detect the MIME type of the file at `path` from its first bytes, with a built-in table of
+/// signatures. Text files are recognized as such, and as scripts, HTML or XML where possible.
+/// Returns `None` if the file can not be read.
+pub fn detect(path: &Path) -> Option<&'static str> {
+    let mut header = Vec::new();
+    File::open(path)
+        .ok()?
+        .take(HEADER_BYTES)
+        .read_to_end(&mut header)
+        .ok()?;
+    Some(detect_bytes(&header))
+}
+
+fn detect_bytes(header: &[u8]) -> &'static str {
+    if header.is_empty() {
+        return "inode/x-empty";
+    }
+
+    let text = is_text(header);
+    let signatures = if text { TEXT_SIGNATURES } else { SIGNATURES };
+    for &(offset, magic, mime) in signatures {
+        if header.get(offset..offset + magic.len()) == Some(magic) {
+            return mime;
+        }
+    }
+
+    if !text {
+        return "application/octet-stream";
+    }
+
+    if let Some(line) = header.strip_prefix(b"#!") {
+        return script_type(line);
+    }
+
+    let text = String::from_utf8_lossy(header).to_ascii_lowercase();
+    let text = text.trim_start_matches('\u{feff}').trim_start();
+    if text.starts_with("<!doctype html") || text.starts_with("<html") {
+        "text/html"
+    } else if text.starts_with("<svg") || (text.starts_with("<?xml") && text.contains("<svg")) {
+        "image/svg+xml"
+    } else if text.starts_with("<?xml") {
+        "text/xml"
+    } else {
+        "text/plain"
+    }
+}
+
+/// Whether the bytes look like text: no NUL bytes, and valid UTF-8 except for a character that
+/// may be cut off at the end.
+fn is_text(bytes: &[u8]) -> bool {
+    if bytes.contains(&0) {
+        return false;
+    }
+    match std::str::from_utf8(bytes) {
+        Ok(_) => true,
+        Err(err) => err.error_len().is_none(),
+    }
+}
+
+/// The type of a script, given the rest of its `#!` line, e.g. `/usr/bin/env python3`.
+fn script_type(line: &[u8]) -> &'static str {
+    let line = String::from_utf8_lossy(line.split(|&b| b == b'\n').next().unwrap_or(&[]));
+    let mut words = line.split_whitespace();
+    let mut program = words.next().unwrap_or("").rsplit('/').next().unwrap_or("");
+    if program == "env" {
+        program = words.find(|w| !w.starts_with('-')).unwrap_or("");
+    }
+
+    // Versioned interpreters like `python3` or `python3.8`.
+    let name = program.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.');
+    INTERPRETERS
+        .iter()
+        .find(|&&(interpreter, _)| interpreter == name)
+        .map_or("text/plain", |&(_, mime)| mime)
+}
+
+/// A pattern for MIME types, e.g. `text/x-shellscript` or `image/*`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MimeFilter {
+    media_type: String,
+    subtype: String,
+}
+
+impl MimeFilter {
+    /// Parse `type/subtype`, where either part can be `*` to match any. Case is ignored.
+    pub fn from_string(s: &str) -> Option<MimeFilter> {
+        let s = s.to_ascii_lowercase();
+        let (media_type, subtype) = s.split_once('/')?;
+        let valid = |part: &str| {
+            !part.is_empty() && !part.contains('/') && !part.chars().any(char::is_whitespace)
+        };
+        if !valid(media_type) || !valid(subtype) {
+            return None;
+        }
+
+        Some(MimeFilter {
+            media_type: media_type.to_string(),
+            subtype: subtype.to_string(),
+        })
+    }
+
+    pub fn matches(&self, mime: &str) -> bool {
+        let (media_type, subtype) = match mime.split_once('/') {
+            Some(parts) => parts,
+            None => return false,
+        };
+        (self.media_type == "*" || self.media_type == media_type)
+            && (self.subtype == "*" || self.subtype == subtype)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detect_signatures() {
+        assert_eq!(detect_bytes(b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR"), "image/png");
+        assert_eq!(detect_bytes(b"\xff\xd8\xff\xe0\0\x10JFIF"), "image/jpeg");
+        assert_eq!(detect_bytes(b"RIFF\0\0\0\0WEBPVP8 "), "image/webp");
+        assert_eq!(detect_bytes(b"%PDF-1.7\n"), "application/pdf");
+        assert_eq!(
+            detect_bytes(b"\x7fELF\x02\x01\x01\0"),
+            "application/x-executable"
+        );
+        assert_eq!(detect_bytes(b"\0\0\0\x18ftypmp42"), "video/mp4");
+
+        let mut tar = vec![0; 512];
+        tar[..8].copy_from_slice(b"file.txt");
+        tar[257..263].copy_from_slice(b"ustar\0");
+        assert_eq!(detect_bytes(&tar), "application/x-tar");
+
+        assert_eq!(detect_bytes(b"\x01\x02\0\x03"), "application/octet-stream");
+        assert_eq!(detect_bytes(b""), "inode/x-empty");
+    }
+
+    #[test]
+    fn detect_text() {
+        assert_eq!(detect_bytes(b"just some notes\n"), "text/plain");
+        assert_eq!(detect_bytes(b"BMW service notes\n"), "text/plain");
+        assert_eq!(detect_bytes("grüße\n".as_bytes()), "text/plain");
+        assert_eq!(detect_bytes(b"#!/bin/sh\necho hi\n"), "text/x-shellscript");
+        assert_eq!(
+            detect_bytes(b"#! /usr/bin/env -S bash -e\n"),
+            "text/x-shellscript"
+        );
+        assert_eq!(
+            detect_bytes(b"#!/usr/bin/python3.8\n"),
+            "text/x-script.python"
+        );
+        assert_eq!(detect_bytes(b"#!/usr/bin/awk -f\n"), "text/plain");
+        assert_eq!(detect_bytes(b"\n  <!DOCTYPE html>\n<html>"), "text/html");
+        assert_eq!(
+            detect_bytes(b"<?xml version=\"1.0\"?>\n<svg>"),
+            "image/svg+xml"
+        );
+        assert_eq!(detect_bytes(b"<?xml version=\"1.0\"?>\n<feed>"), "text/xml");
+
+        // A multi-byte character that is cut off at the end of the header.
+        assert_eq!(detect_bytes(&"grüße".as_bytes()[..3]), "text/plain");
+    }
+
+    #[test]
+    fn match_patterns() {
+        let image = MimeFilter::from_string("image/*").unwrap();
+        assert!(image.matches("image/png"));
+        assert!(!image.matches("text/plain"));
+
+        let script = MimeFilter::from_string("Text/X-Shellscript").unwrap();
+        assert!(script.matches("text/x-shellscript"));
+        assert!(!script.matches("text/plain"));
+
+        assert!(MimeFilter::from_string("*/*").unwrap().matches("font/woff"));
+
+        assert_eq!(MimeFilter::from_string("image"), None);
+        assert_eq!(MimeFilter::from_string("image/"), None);
+        assert_eq!(MimeFilter::from_string("a/b/c"), None);
+    }
+}
@@ -1,13 +1,35 @@
-pub use self::size::SizeFilter;
-pub use self::time::TimeFilter;
+pub use self::checksum::{ChecksumFilter, ChecksumKind};
+pub use self::content::ContentFilter;
+pub use self::expr::FilterExpr;
+pub use self::link::LinkTargetFilter;
+pub use self::mime::{detect as detect_mime_type, MimeFilter};
+pub use self::size::{SizeFilter, SizeKind};
+pub use self::time::{TimeFilter, Timestamp};
+#[cfg(unix)]
+pub use self::access::{Access, AccessFilter, AccessUser};
+#[cfg(unix)]
+pub use self::inode::{InodeFilter, LinksFilter};
 #[cfg(unix)]
 pub use self::owner::OwnerFilter;
 #[cfg(unix)]
 pub use self::perm::PermissionFilter;
+#[cfg(unix)]
+pub use self::xattr::{Xattr, XattrFilter};
 
+mod checksum;
+mod content;
+mod expr;
+mod link;
+mod mime;
 mod size;
 mod time;
 #[cfg(unix)]
+mod access;
+#[cfg(unix)]
+mod inode;
+#[cfg(unix)]
 mod owner;
 #[cfg(unix)]
 mod perm;
+#[cfg(unix)]
+mod xattr;
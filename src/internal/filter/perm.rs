@@ -10,6 +10,10 @@ pub enum PermissionFilter {
 }
 
 impl PermissionFilter {
+    /// Parse an octal mode like `644`, `-4000` or `/111`, or a comma-separated list of symbolic
+    /// clauses like `u+x,o-w`, `g=rw` or `setuid`. The clauses are conditions that all have to
+    /// hold: `+` bits are set, `-` bits are not, and `=` gives the exact `rwx` bits of a class.
+    /// With the `/` prefix, only `+` clauses are allowed and any of their bits has to be set.
     pub fn from_string(s: &str) -> Option<Self> {
         let mut bytes = s.as_bytes();
 
@@ -18,11 +22,35 @@ impl PermissionFilter {
             _ => false,
         };
 
+        // `-w` is a clause without a class, like `+x`, while the `-` of `-u+x` or `-4000` is the
+        // prefix that asks for all of the bits.
+        let clause = match bytes.first()? {
+            &b'-' => parse_symbolic(bytes),
+            _ => None,
+        };
+
         let mode = match bytes.first()? {
-            byte if b"=-/".contains(byte) => { bytes = &bytes[1..]; *byte },
+            byte if clause.is_none() && b"=-/".contains(byte) => { bytes = &bytes[1..]; *byte },
             _ => b'=',
         };
 
+        if !bytes.iter().all(|&b| b == b'.' || b.is_ascii_digit()) {
+            let (bits, mask, only_added) = match clause {
+                Some(clause) => clause,
+                None => parse_symbolic(bytes)?,
+            };
+            return match (negated, mode) {
+                (false, b'/') if only_added => Some(Self::Any(bits)),
+                (true, b'/') if only_added => Some(Self::NotAny(bits)),
+                (_, b'/') => None,
+
+                (false, _) if bits == mask => Some(Self::All(bits)),
+                (true, _) if bits == mask => Some(Self::NotAll(bits)),
+                (false, _) => Some(Self::Exact(bits, mask)),
+                (true, _) => Some(Self::NotExact(bits, mask)),
+            };
+        }
+
         if !(3..=4).contains(&bytes.len()) {
             return None;
         }
@@ -64,3 +92,165 @@ impl PermissionFilter {
         }
     }
 }
+
+/// The bit that a permission letter of a symbolic mode stands for, for the user (`u`), group (`g`)
+/// or others (`o`), or `0` if it has none for that class.
+fn class_bit(class: u8, perm: u8) -> u32 {
+    let shift = match class {
+        b'u' => 6,
+        b'g' => 3,
+        _ => 0,
+    };
+    match (class, perm) {
+        (_, b'r') => 0o4 << shift,
+        (_, b'w') => 0o2 << shift,
+        (_, b'x') => 0o1 << shift,
+        (b'u', b's') => 0o4000,
+        (b'g', b's') => 0o2000,
+        (b'o', b't') => 0o1000,
+        _ => 0,
+    }
+}
+
+/// Parse comma-separated symbolic clauses into the bits that the permissions have to match under
+/// a mask, and whether all of the clauses only add bits (`+`). Returns `None` for invalid or
+/// contradicting clauses, like `u+x,u-x`.
+fn parse_symbolic(bytes: &[u8]) -> Option<(u32, u32, bool)> {
+    let (mut bits, mut mask, mut only_added) = (0u32, 0u32, true);
+    let mut require = |required: u32, required_mask: u32| {
+        let overlap = mask & required_mask;
+        if bits & overlap != required & overlap {
+            return None;
+        }
+        bits |= required;
+        mask |= required_mask;
+        Some(())
+    };
+
+    for clause in bytes.split(|&b| b == b',') {
+        match clause {
+            b"setuid" => { require(0o4000, 0o4000)?; continue; },
+            b"setgid" => { require(0o2000, 0o2000)?; continue; },
+            b"sticky" => { require(0o1000, 0o1000)?; continue; },
+            _ => (),
+        }
+
+        let who_len = clause.iter().take_while(|b| b"ugoa".contains(b)).count();
+        let (who, mut rest) = clause.split_at(who_len);
+        let classes: &[u8] = if who.is_empty() || who.contains(&b'a') { b"ugo" } else { who };
+        let bits_of = |perm| classes.iter().fold(0, |acc, &class| acc | class_bit(class, perm));
+
+        if rest.is_empty() {
+            return None;
+        }
+        while let Some((&op, tail)) = rest.split_first() {
+            if !b"+-=".contains(&op) {
+                return None;
+            }
+            let len = tail.iter().take_while(|b| !b"+-=".contains(b)).count();
+            // Only `=` may go without letters: `g=` clears the bits of the group.
+            if len == 0 && op != b'=' {
+                return None;
+            }
+
+            let mut perm_bits = 0;
+            for &perm in &tail[..len] {
+                match bits_of(perm) {
+                    0 => return None,
+                    bit => perm_bits |= bit,
+                }
+            }
+
+            match op {
+                b'+' => require(perm_bits, perm_bits)?,
+                b'-' => { require(0, perm_bits)?; only_added = false; },
+                _ => {
+                    let rwx = bits_of(b'r') | bits_of(b'w') | bits_of(b'x');
+                    require(perm_bits, rwx | perm_bits)?;
+                    only_added = false;
+                },
+            }
+            rest = &tail[len..];
+        }
+    }
+
+    Some((bits, mask, only_added))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::PermissionFilter::*;
+
+    macro_rules! gen_perm_tests {
+        ($($name:ident: $value:expr => $result:expr,)*) => {
+            $(
+                #[test]
+                fn $name() {
+                    assert_eq!(PermissionFilter::from_string($value), $result);
+                }
+            )*
+        }
+    }
+
+    gen_perm_tests! {
+        octal_exact:    "644"       => Some(Exact(0o644, 0o777)),
+        octal_wildcard: "6.4"       => Some(Exact(0o604, 0o707)),
+        octal_all:      "-4000"     => Some(All(0o4000)),
+        octal_any:      "/111"      => Some(Any(0o111)),
+        octal_not:      "!-002"     => Some(NotAll(0o002)),
+        octal_short:    "64"        => None,
+        octal_digit:    "648"       => None,
+
+        user_exec:      "u+x"       => Some(All(0o100)),
+        all_read:       "a+r"       => Some(All(0o444)),
+        no_who:         "+x"        => Some(All(0o111)),
+        classes:        "ug+rw"     => Some(All(0o660)),
+        other_no_write: "o-w"       => Some(Exact(0, 0o002)),
+        no_who_write:   "-w"        => Some(Exact(0, 0o222)),
+        no_who_exec:    "-x"        => Some(Exact(0, 0o111)),
+        not_no_write:   "!-w"       => Some(NotExact(0, 0o222)),
+        no_who_several: "-w,u+x"    => Some(Exact(0o100, 0o322)),
+        group_exact:    "g=rw"      => Some(Exact(0o060, 0o070)),
+        group_none:     "g="        => Some(Exact(0, 0o070)),
+        several:        "u+x,o-w"   => Some(Exact(0o100, 0o102)),
+        ops_in_clause:  "u+r-w"     => Some(Exact(0o400, 0o600)),
+        negated:        "!o-w"      => Some(NotExact(0, 0o002)),
+        all_prefix:     "-u+x,g+x"  => Some(All(0o110)),
+        any_prefix:     "/u+x,g+x"  => Some(Any(0o110)),
+        not_any:        "!/a+w"     => Some(NotAny(0o222)),
+        any_no_minus:   "/o-w"      => None,
+
+        setuid:         "setuid"    => Some(All(0o4000)),
+        named_bits:     "setgid,sticky" => Some(All(0o3000)),
+        special:        "u+s,o+t"   => Some(All(0o5000)),
+        setuid_letter:  "a+s"       => Some(All(0o6000)),
+        exact_special:  "u=rwxs"    => Some(All(0o4700)),
+
+        contradiction:  "u+x,u-x"   => None,
+        sticky_user:    "u+t"       => None,
+        no_op:          "u"         => None,
+        bad_letter:     "u+q"       => None,
+        bad_class:      "z+x"       => None,
+        empty_clause:   "u+x,"      => None,
+        only_minus:     "-"         => None,
+        only_plus:      "+"         => None,
+        no_letters:     "u+"        => None,
+        all_no_letters: "-u+"       => None,
+    }
+
+    #[test]
+    fn match_symbolic() {
+        let filter = |s| PermissionFilter::from_string(s).unwrap();
+
+        assert!(filter("u+x,o-w").matches(0o100755));
+        assert!(!filter("u+x,o-w").matches(0o100757));
+        assert!(!filter("u+x,o-w").matches(0o100644));
+        assert!(filter("g=rw").matches(0o664));
+        assert!(!filter("g=rw").matches(0o674));
+        assert!(filter("setuid").matches(0o4755));
+        assert!(!filter("setuid").matches(0o2755));
+        assert!(filter("/a+x").matches(0o001));
+        assert!(!filter("!/a+x").matches(0o001));
+    }
+}
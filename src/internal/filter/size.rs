@@ -1,14 +1,55 @@
+use std::convert::TryFrom;
+use std::fs::Metadata;
+
 use lazy_static::lazy_static;
 use regex::Regex;
 
 lazy_static! {
-    static ref SIZE_CAPTURES: Regex = { Regex::new(r"(?i)^([+-])(\d+)(b|[kmgt]i?b?)$").unwrap() };
+    /// A limit (`+1k`, `-1.5M`, `=512b`) or a closed range (`10k..2M`).
+    static ref SIZE_CAPTURES: Regex = Regex::new(
+        r"(?ix)^(?:
+            (?P<limit>[+=-]) (?P<size>\d+(?:\.\d+)?) (?P<unit>b|[kmgt]i?b?)
+          | (?P<min>\d+(?:\.\d+)?) (?P<min_unit>b|[kmgt]i?b?)
+            \.\.
+            (?P<max>\d+(?:\.\d+)?) (?P<max_unit>b|[kmgt]i?b?)
+        )$"
+    )
+    .unwrap();
+}
+
+/// Which size of an entry is compared and reported.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum SizeKind {
+    /// The length of the contents (`st_size`).
+    #[default]
+    Apparent,
+    /// The space allocated on disk (`st_blocks * 512`), which is smaller than the apparent size
+    /// for sparse files. Same as `Apparent` on platforms other than Unix.
+    Allocated,
+}
+
+impl SizeKind {
+    pub fn of(self, metadata: &Metadata) -> u64 {
+        match self {
+            SizeKind::Apparent => metadata.len(),
+            #[cfg(unix)]
+            SizeKind::Allocated => {
+                use std::os::unix::fs::MetadataExt;
+                metadata.blocks() * 512
+            }
+            #[cfg(not(unix))]
+            SizeKind::Allocated => metadata.len(),
+        }
+    }
 }
 
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum SizeFilter {
     Max(u64),
     Min(u64),
+    Exact(u64),
+    /// A closed range, including both ends.
+    Between(u64, u64),
 }
 
 // SI prefixes (powers of 10)
@@ -25,44 +66,76 @@ const TEBI: u64 = GIBI * 1024;
 
 impl SizeFilter {
     pub fn from_string(s: &str) -> Option<Self> {
-        if !SIZE_CAPTURES.is_match(s) {
-            return None;
-        }
-
         let captures = SIZE_CAPTURES.captures(s)?;
-        let limit_kind = captures.get(1).map_or("+", |m| m.as_str());
-        let quantity = captures
-            .get(2)
-            .and_then(|v| v.as_str().parse::<u64>().ok())?;
-
-        let multiplier = match &captures.get(3).map_or("b", |m| m.as_str()).to_lowercase()[..] {
-            v if v.starts_with("ki") => KIBI,
-            v if v.starts_with('k') => KILO,
-            v if v.starts_with("mi") => MEBI,
-            v if v.starts_with('m') => MEGA,
-            v if v.starts_with("gi") => GIBI,
-            v if v.starts_with('g') => GIGA,
-            v if v.starts_with("ti") => TEBI,
-            v if v.starts_with('t') => TERA,
-            "b" => 1,
-            _ => return None,
-        };
 
-        let size = quantity * multiplier;
-        Some(match limit_kind {
-            "+" => SizeFilter::Min(size),
-            _ => SizeFilter::Max(size),
-        })
+        if let Some(limit_kind) = captures.name("limit") {
+            let size = to_bytes(&captures["size"], &captures["unit"])?;
+            return Some(match limit_kind.as_str() {
+                "+" => SizeFilter::Min(size),
+                "=" => SizeFilter::Exact(size),
+                _ => SizeFilter::Max(size),
+            });
+        }
+
+        let min = to_bytes(&captures["min"], &captures["min_unit"])?;
+        let max = to_bytes(&captures["max"], &captures["max_unit"])?;
+        if min > max {
+            return None;
+        }
+        Some(SizeFilter::Between(min, max))
     }
 
     pub fn is_within(&self, size: u64) -> bool {
         match *self {
             SizeFilter::Max(limit) => size <= limit,
             SizeFilter::Min(limit) => size >= limit,
+            SizeFilter::Exact(limit) => size == limit,
+            SizeFilter::Between(min, max) => min <= size && size <= max,
         }
     }
 }
 
+/// Convert a (possibly fractional) number of units to bytes, rounded to the nearest byte. Returns
+/// `None` for a fraction of a byte (`1.5b`) or if the result does not fit into 64 bits.
+fn to_bytes(quantity: &str, unit: &str) -> Option<u64> {
+    let multiplier = match &unit.to_lowercase()[..] {
+        v if v.starts_with("ki") => KIBI,
+        v if v.starts_with('k') => KILO,
+        v if v.starts_with("mi") => MEBI,
+        v if v.starts_with('m') => MEGA,
+        v if v.starts_with("gi") => GIBI,
+        v if v.starts_with('g') => GIGA,
+        v if v.starts_with("ti") => TEBI,
+        v if v.starts_with('t') => TERA,
+        "b" => 1,
+        _ => return None,
+    };
+
+    let (integer, fraction) = match quantity.find('.') {
+        Some(i) => (&quantity[..i], &quantity[i + 1..]),
+        None => (quantity, ""),
+    };
+
+    // Computed exactly rather than with floating point, so that e.g. `1.1k` is 1100 bytes.
+    let mut size = integer
+        .parse::<u128>()
+        .ok()?
+        .checked_mul(u128::from(multiplier))?;
+    if !fraction.is_empty() {
+        let denominator = 10u128.checked_pow(fraction.len() as u32)?;
+        let numerator = fraction
+            .parse::<u128>()
+            .ok()?
+            .checked_mul(u128::from(multiplier))?;
+        if multiplier == 1 && numerator % denominator != 0 {
+            return None;
+        }
+        size = size.checked_add((numerator + denominator / 2) / denominator)?;
+    }
+
+    u64::try_from(size).ok()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -153,6 +226,18 @@ mod tests {
         tebi_plus_suffix_upper:   ("+1TiB",   SizeFilter::Min(1099511627776)),
         tebi_minus_upper:         ("-1Ti",    SizeFilter::Max(1099511627776)),
         tebi_minus_suffix_upper:  ("-1TIB",   SizeFilter::Max(1099511627776)),
+        exact_byte:               ("=1b",     SizeFilter::Exact(1)),
+        exact_kibi:               ("=4ki",    SizeFilter::Exact(4096)),
+        fraction_giga:            ("+1.5G",   SizeFilter::Min(1500000000)),
+        fraction_kilo:            ("-1.1k",   SizeFilter::Max(1100)),
+        fraction_kibi:            ("=0.5KiB", SizeFilter::Exact(512)),
+        fraction_trailing_zeros:  ("+2.50m",  SizeFilter::Min(2500000)),
+        fraction_rounded_down:    ("+1.1Ki",  SizeFilter::Min(1126)),
+        fraction_rounded_up:      ("=1.0005k", SizeFilter::Exact(1001)),
+        fraction_below_byte:      ("-0.0001k", SizeFilter::Max(0)),
+        range:                    ("10k..2M", SizeFilter::Between(10000, 2000000)),
+        range_fraction:           ("1.5ki..2ki", SizeFilter::Between(1536, 2048)),
+        range_single_value:       ("1k..1000b", SizeFilter::Between(1000, 1000)),
     }
 
     /// Invalid parse testing
@@ -181,6 +266,13 @@ mod tests {
         ensure_invalid_unit_returns_none_3: "+1Mv",
         ensure_bib_format_returns_none: "+1bib",
         ensure_bb_format_returns_none: "+1bb",
+        ensure_fractional_bytes_returns_none: "+1.5b",
+        ensure_missing_fraction_returns_none: "+1.k",
+        ensure_overflow_returns_none: "+99999999999t",
+        ensure_reversed_range_returns_none: "2M..10k",
+        ensure_range_with_symbol_returns_none: "+10k..2M",
+        ensure_open_range_returns_none: "10k..",
+        ensure_range_without_unit_returns_none: "10..20k",
     }
 
     #[test]
@@ -206,4 +298,21 @@ mod tests {
         let f = SizeFilter::from_string("+1K").unwrap();
         assert!(f.is_within(1000));
     }
+
+    #[test]
+    fn is_within_exact() {
+        let f = SizeFilter::from_string("=1k").unwrap();
+        assert!(f.is_within(1000));
+        assert!(!f.is_within(999));
+        assert!(!f.is_within(1001));
+    }
+
+    #[test]
+    fn is_within_range() {
+        let f = SizeFilter::from_string("1k..2k").unwrap();
+        assert!(!f.is_within(999));
+        assert!(f.is_within(1000));
+        assert!(f.is_within(2000));
+        assert!(!f.is_within(2001));
+    }
 }
@@ -1,36 +1,316 @@
-use std::time::SystemTime;
+use std::fs::{self, Metadata};
+use std::io;
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+use chrono::{
+    DateTime, Datelike, Duration as ChronoDuration, FixedOffset, Local, Months, NaiveDate,
+    NaiveDateTime, TimeZone, Weekday,
+};
+use lazy_static::lazy_static;
+use regex::Regex;
+
+lazy_static! {
+    /// A date or point in time: `2024`, `2024-05`, `2024-05-17`, `2024-05-17 10:00`,
+    /// `2024-05-17T10:00:00.5+02:00`, `2024-05-17 10:00 local`, ..
+    static ref DATE_TIME_CAPTURES: Regex = Regex::new(
+        r"(?ix)^
+        (?P<year>\d{4})
+        (?: -(?P<month>\d{2})
+            (?: -(?P<day>\d{2})
+                (?: [T\ ](?P<hour>\d{2}):(?P<minute>\d{2})
+                    (?: :(?P<second>\d{2}) (?: \.(?P<fraction>\d{1,9}) )? )?
+                )?
+                \ ?(?P<zone> Z | UTC | local | [+-]\d{2}(?: :?\d{2} )? )?
+            )?
+        )?
+        $"
+    )
+    .unwrap();
+}
+
+/// The timestamp of an entry that a `TimeFilter` is applied to.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Timestamp {
+    /// The last modification of the contents (mtime).
+    Modified,
+    /// The last access (atime).
+    Accessed,
+    /// The last change of the inode, e.g. of its permissions or owner (ctime). Unix only.
+    InodeChanged,
+    /// The creation of the file (birth time). On Linux, it is read with `statx`, which needs
+    /// support from the kernel and the file system.
+    Created,
+}
+
+impl Timestamp {
+    /// Read the timestamp from the metadata, if it is available on this platform.
+    pub fn of(self, metadata: &Metadata) -> Option<SystemTime> {
+        match self {
+            Timestamp::Modified => metadata.modified().ok(),
+            Timestamp::Accessed => metadata.accessed().ok(),
+            Timestamp::InodeChanged => inode_changed(metadata),
+            Timestamp::Created => metadata.created().ok(),
+        }
+    }
+}
+
+#[cfg(unix)]
+fn inode_changed(metadata: &Metadata) -> Option<SystemTime> {
+    use std::os::unix::fs::MetadataExt;
+    use std::time::UNIX_EPOCH;
+
+    let nanos = Duration::from_nanos(metadata.ctime_nsec() as u64);
+    if metadata.ctime() >= 0 {
+        UNIX_EPOCH.checked_add(Duration::from_secs(metadata.ctime() as u64) + nanos)
+    } else {
+        UNIX_EPOCH.checked_sub(Duration::from_secs(metadata.ctime().unsigned_abs()) - nanos)
+    }
+}
+
+#[cfg(not(unix))]
+fn inode_changed(_: &Metadata) -> Option<SystemTime> {
+    None
+}
 
 /// Filter based on time ranges.
 #[derive(Debug, PartialEq)]
 pub enum TimeFilter {
-    Before(SystemTime),
-    After(SystemTime),
+    Before(Timestamp, SystemTime),
+    After(Timestamp, SystemTime),
 }
 
 impl TimeFilter {
+    /// Parse a point in time. This is either
+    ///
+    /// * a duration before the reference time (`10h`, `2weeks`),
+    /// * a day relative to the reference time (`today`, `yesterday`, `last monday`), starting at
+    ///   midnight in the local time zone, or
+    /// * a date or point in time (`2024-05`, `2024-05-17`, `2024-05-17 10:00:00`), which is in
+    ///   UTC unless a zone is given (`+02:00`, or `local` for the local time zone).
     fn from_str(ref_time: &SystemTime, s: &str) -> Option<SystemTime> {
+        TimeFilter::parse(ref_time, s, false)
+    }
+
+    /// Parse the end of an interval. Like `from_str`, but a day or date without a time
+    /// (`yesterday`, `2024-05-17`, `2024-05`, `2024`) includes the whole day, month or year.
+    fn end_from_str(ref_time: &SystemTime, s: &str) -> Option<SystemTime> {
+        TimeFilter::parse(ref_time, s, true)
+    }
+
+    fn parse(ref_time: &SystemTime, s: &str, to_end: bool) -> Option<SystemTime> {
+        let s = s.trim();
         humantime::parse_duration(s)
-            .map(|duration| *ref_time - duration)
-            .or_else(|_| humantime::parse_rfc3339_weak(s))
             .ok()
+            .and_then(|duration| ref_time.checked_sub(duration))
+            .or_else(|| parse_relative_day(ref_time, s, to_end))
+            .or_else(|| parse_date_time(s, to_end))
+    }
+
+    /// Parse a closed interval `START..END` into a filter for each end, e.g.
+    /// `2024-01-01..2024-02-01`. An end without a time includes that whole day (see
+    /// `end_from_str`). Returns `None` unless the start is before the end.
+    pub fn between(ref_time: &SystemTime, s: &str) -> Option<(TimeFilter, TimeFilter)> {
+        let mut parts = s.splitn(2, "..");
+        let start = TimeFilter::from_str(ref_time, parts.next()?)?;
+        let end = TimeFilter::end_from_str(ref_time, parts.next()?)?;
+        if start > end {
+            return None;
+        }
+        Some((
+            TimeFilter::After(Timestamp::Modified, start),
+            TimeFilter::Before(Timestamp::Modified, end),
+        ))
     }
 
+    /// A filter on the modification time. Use `of` to test another timestamp.
     pub fn before(ref_time: &SystemTime, s: &str) -> Option<TimeFilter> {
-        TimeFilter::from_str(ref_time, s).map(TimeFilter::Before)
+        TimeFilter::from_str(ref_time, s).map(|t| TimeFilter::Before(Timestamp::Modified, t))
     }
 
+    /// A filter on the modification time. Use `of` to test another timestamp.
     pub fn after(ref_time: &SystemTime, s: &str) -> Option<TimeFilter> {
-        TimeFilter::from_str(ref_time, s).map(TimeFilter::After)
+        TimeFilter::from_str(ref_time, s).map(|t| TimeFilter::After(Timestamp::Modified, t))
+    }
+
+    /// Match entries whose timestamp is strictly newer than the same timestamp of the file at
+    /// `path`. The reference timestamp is read once, following symlinks.
+    pub fn newer_than(path: &Path, timestamp: Timestamp) -> io::Result<TimeFilter> {
+        let reference = reference_time(path, timestamp)?;
+        // Timestamps have a resolution of (at best) one nanosecond, so this excludes entries
+        // that are exactly as old as the reference.
+        Ok(TimeFilter::After(
+            timestamp,
+            reference + Duration::from_nanos(1),
+        ))
+    }
+
+    /// Match entries whose timestamp is strictly older than the same timestamp of the file at
+    /// `path`. The reference timestamp is read once, following symlinks.
+    pub fn older_than(path: &Path, timestamp: Timestamp) -> io::Result<TimeFilter> {
+        let reference = reference_time(path, timestamp)?;
+        let limit = reference
+            .checked_sub(Duration::from_nanos(1))
+            .unwrap_or(reference);
+        Ok(TimeFilter::Before(timestamp, limit))
+    }
+
+    /// The same filter, applied to the given timestamp instead.
+    pub fn of(self, timestamp: Timestamp) -> TimeFilter {
+        match self {
+            TimeFilter::Before(_, limit) => TimeFilter::Before(timestamp, limit),
+            TimeFilter::After(_, limit) => TimeFilter::After(timestamp, limit),
+        }
+    }
+
+    pub fn timestamp(&self) -> Timestamp {
+        match self {
+            TimeFilter::Before(timestamp, _) | TimeFilter::After(timestamp, _) => *timestamp,
+        }
     }
 
     pub fn applies_to(&self, t: &SystemTime) -> bool {
         match self {
-            TimeFilter::Before(limit) => t <= limit,
-            TimeFilter::After(limit) => t >= limit,
+            TimeFilter::Before(_, limit) => t <= limit,
+            TimeFilter::After(_, limit) => t >= limit,
+        }
+    }
+
+    /// Whether the timestamp of an entry is within the range. Entries for which the timestamp
+    /// is not available never match.
+    pub fn matches(&self, metadata: &Metadata) -> bool {
+        self.timestamp()
+            .of(metadata)
+            .is_some_and(|t| self.applies_to(&t))
+    }
+}
+
+fn reference_time(path: &Path, timestamp: Timestamp) -> io::Result<SystemTime> {
+    timestamp.of(&fs::metadata(path)?).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::Unsupported,
+            "the timestamp is not available on this platform or file system",
+        )
+    })
+}
+
+/// Midnight (local time) of `today`, `yesterday` or the most recent given weekday before today
+/// (`last monday`), or the last moment of that day if `to_end` is set.
+fn parse_relative_day(ref_time: &SystemTime, s: &str, to_end: bool) -> Option<SystemTime> {
+    let s = s.to_lowercase();
+    let today = DateTime::<Local>::from(*ref_time).date_naive();
+
+    let day = match s.as_str() {
+        "now" => return Some(*ref_time),
+        "today" => today,
+        "yesterday" => today.pred_opt()?,
+        _ => {
+            let weekday: Weekday = s.strip_prefix("last ")?.trim().parse().ok()?;
+            let days_back = match (today.weekday().num_days_from_monday() + 7
+                - weekday.num_days_from_monday())
+                % 7
+            {
+                0 => 7,
+                n => n,
+            };
+            today - ChronoDuration::days(i64::from(days_back))
+        }
+    };
+
+    if to_end {
+        let next_day = local_to_system_time(day.succ_opt()?.and_hms_opt(0, 0, 0)?)?;
+        return next_day.checked_sub(Duration::from_nanos(1));
+    }
+    local_to_system_time(day.and_hms_opt(0, 0, 0)?)
+}
+
+/// Parse a date or point in time. Without a time, it is the start of the year, month or day, or
+/// its last moment if `to_end` is set.
+fn parse_date_time(s: &str, to_end: bool) -> Option<SystemTime> {
+    let captures = DATE_TIME_CAPTURES.captures(s)?;
+    let number = |name, default| {
+        captures
+            .name(name)
+            .map_or(Some(default), |m| m.as_str().parse().ok())
+    };
+
+    let date = NaiveDate::from_ymd_opt(
+        captures["year"].parse().ok()?,
+        number("month", 1)?,
+        number("day", 1)?,
+    )?;
+    let nanos = match captures.name("fraction") {
+        Some(m) => format!("{:0<9}", m.as_str()).parse().ok()?,
+        None => 0,
+    };
+    let mut date_time = date.and_hms_nano_opt(
+        number("hour", 0)?,
+        number("minute", 0)?,
+        number("second", 0)?,
+        nanos,
+    )?;
+    let to_end = to_end && captures.name("hour").is_none();
+    if to_end {
+        let next = match (captures.name("month"), captures.name("day")) {
+            (None, _) => NaiveDate::from_ymd_opt(date.year() + 1, 1, 1)?,
+            (Some(_), None) => date.checked_add_months(Months::new(1))?,
+            (Some(_), Some(_)) => date.succ_opt()?,
+        };
+        date_time = next.and_hms_opt(0, 0, 0)?;
+    }
+
+    let time = match captures.name("zone") {
+        Some(zone) if zone.as_str().eq_ignore_ascii_case("local") => {
+            local_to_system_time(date_time)?
         }
+        zone => {
+            let offset = zone.map_or(Some(0), |zone| parse_utc_offset(zone.as_str()))?;
+            let date_time = FixedOffset::east_opt(offset)?
+                .from_local_datetime(&date_time)
+                .single()?;
+            SystemTime::from(date_time)
+        }
+    };
+    if to_end {
+        time.checked_sub(Duration::from_nanos(1))
+    } else {
+        Some(time)
     }
 }
 
+/// The offset from UTC in seconds, e.g. for `Z`, `UTC`, `+02:00`, `-0530` or `+01`.
+fn parse_utc_offset(zone: &str) -> Option<i32> {
+    if zone.eq_ignore_ascii_case("z") || zone.eq_ignore_ascii_case("utc") {
+        return Some(0);
+    }
+
+    let sign = if zone.starts_with('-') { -1 } else { 1 };
+    let digits = zone[1..].replace(':', "");
+    let hours: i32 = digits.get(..2)?.parse().ok()?;
+    let minutes: i32 = match digits.get(2..) {
+        Some("") | None => 0,
+        Some(minutes) => minutes.parse().ok()?,
+    };
+    if hours > 23 || minutes > 59 {
+        return None;
+    }
+    Some(sign * (hours * 3600 + minutes * 60))
+}
+
+/// Interpret a date and time in the local time zone. If it falls into a gap, for example when
+/// clocks are set forward for daylight saving time, the first valid time after it is used.
+fn local_to_system_time(date_time: NaiveDateTime) -> Option<SystemTime> {
+    Local
+        .from_local_datetime(&date_time)
+        .earliest()
+        .or_else(|| {
+            Local
+                .from_local_datetime(&(date_time + ChronoDuration::hours(1)))
+                .earliest()
+        })
+        .map(SystemTime::from)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -76,4 +356,164 @@ mod tests {
             .unwrap()
             .applies_to(&t1m_ago));
     }
+
+    fn local(s: &str) -> SystemTime {
+        let date_time = NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S").unwrap();
+        SystemTime::from(Local.from_local_datetime(&date_time).unwrap())
+    }
+
+    fn utc(s: &str) -> SystemTime {
+        humantime::parse_rfc3339_weak(s).unwrap()
+    }
+
+    #[test]
+    fn parse_dates_and_times() {
+        let ref_time = SystemTime::now();
+        let parse = |s| TimeFilter::from_str(&ref_time, s);
+
+        assert_eq!(parse("2024-05"), Some(utc("2024-05-01 00:00:00")));
+        assert_eq!(parse("2024"), Some(utc("2024-01-01 00:00:00")));
+        assert_eq!(parse("2024-05-17"), Some(utc("2024-05-17 00:00:00")));
+        assert_eq!(parse("2024-05-17 10:20"), Some(utc("2024-05-17 10:20:00")));
+        assert_eq!(
+            parse("2024-05-17T10:20:30"),
+            Some(utc("2024-05-17 10:20:30"))
+        );
+        assert_eq!(
+            parse("2024-05-17 local"),
+            Some(local("2024-05-17 00:00:00"))
+        );
+        assert_eq!(
+            parse("2024-05-17 10:20 local"),
+            Some(local("2024-05-17 10:20:00"))
+        );
+
+        let utc = humantime::parse_rfc3339("2024-05-17T10:20:30Z").unwrap();
+        assert_eq!(parse("2024-05-17 10:20:30Z"), Some(utc));
+        assert_eq!(parse("2024-05-17 10:20:30 UTC"), Some(utc));
+        assert_eq!(parse("2024-05-17T12:20:30+02:00"), Some(utc),);
+        assert_eq!(parse("2024-05-17 05:50:30-0430"), Some(utc));
+        assert_eq!(
+            parse("2024-05-17 10:20:30.25Z"),
+            Some(utc + std::time::Duration::from_millis(250))
+        );
+
+        assert_eq!(parse("2024-13"), None);
+        assert_eq!(parse("2024-02-30"), None);
+        assert_eq!(parse("2024-05-17 25:00"), None);
+        assert_eq!(parse("2024-05-17 10:00+24:00"), None);
+        assert_eq!(parse("17.05.2024"), None);
+    }
+
+    #[test]
+    fn parse_relative_days() {
+        // A Friday.
+        let ref_time = local("2024-05-17 15:30:00");
+        let parse = |s| TimeFilter::from_str(&ref_time, s);
+
+        assert_eq!(parse("now"), Some(ref_time));
+        assert_eq!(parse("today"), Some(local("2024-05-17 00:00:00")));
+        assert_eq!(parse("Yesterday"), Some(local("2024-05-16 00:00:00")));
+        assert_eq!(parse("last monday"), Some(local("2024-05-13 00:00:00")));
+        assert_eq!(parse("last thu"), Some(local("2024-05-16 00:00:00")));
+        assert_eq!(parse("last friday"), Some(local("2024-05-10 00:00:00")));
+        assert_eq!(parse("last saturday"), Some(local("2024-05-11 00:00:00")));
+        assert_eq!(parse("monday"), None);
+        assert_eq!(parse("last month"), None);
+
+        assert_eq!(
+            parse("2h"),
+            Some(ref_time - std::time::Duration::from_secs(2 * 60 * 60))
+        );
+    }
+
+    #[test]
+    fn closed_intervals() {
+        let ref_time = SystemTime::now();
+        let (start, end) = TimeFilter::between(&ref_time, "2024-01-01..2024-02-01").unwrap();
+        assert!(start.applies_to(&utc("2024-01-15 12:00:00")));
+        assert!(end.applies_to(&utc("2024-01-15 12:00:00")));
+        assert!(!start.applies_to(&utc("2023-12-31 23:59:59")));
+        assert!(end.applies_to(&utc("2024-02-01 23:59:59")));
+        assert!(!end.applies_to(&utc("2024-02-02 00:00:00")));
+
+        let (_, end) = TimeFilter::between(&ref_time, "2024-01-01..2024-02-01 12:00").unwrap();
+        assert!(end.applies_to(&utc("2024-02-01 12:00:00")));
+        assert!(!end.applies_to(&utc("2024-02-01 12:00:01")));
+
+        let (_, end) = TimeFilter::between(&ref_time, "2023..2024-02").unwrap();
+        assert!(end.applies_to(&utc("2024-02-29 23:59:59")));
+        assert!(!end.applies_to(&utc("2024-03-01 00:00:00")));
+
+        let (_, end) = TimeFilter::between(&ref_time, "2023..2024").unwrap();
+        assert!(end.applies_to(&utc("2024-12-31 23:59:59")));
+        assert!(!end.applies_to(&utc("2025-01-01 00:00:00")));
+
+        let (start, end) = TimeFilter::between(&ref_time, "2024-02-01..2024-02-01").unwrap();
+        assert!(start.applies_to(&utc("2024-02-01 18:00:00")));
+        assert!(end.applies_to(&utc("2024-02-01 18:00:00")));
+
+        let (start, end) =
+            TimeFilter::between(&ref_time, "2024-02-01 local..2024-02-01 local").unwrap();
+        assert!(start.applies_to(&local("2024-02-01 00:00:00")));
+        assert!(end.applies_to(&local("2024-02-01 23:59:59")));
+        assert!(!end.applies_to(&local("2024-02-02 00:00:00")));
+
+        let (start, end) = TimeFilter::between(&ref_time, "1week..yesterday").unwrap();
+        assert!(start.applies_to(&(ref_time - std::time::Duration::from_secs(3 * 86400))));
+        assert!(end.applies_to(&(ref_time - std::time::Duration::from_secs(3 * 86400))));
+        let today = DateTime::<Local>::from(ref_time).date_naive();
+        let midnight = local_to_system_time(today.and_hms_opt(0, 0, 0).unwrap()).unwrap();
+        let yesterday_evening = midnight - std::time::Duration::from_secs(1);
+        assert!(end.applies_to(&yesterday_evening));
+        assert!(!end.applies_to(&ref_time));
+
+        assert!(TimeFilter::between(&ref_time, "2024-02-01..2024-01-01").is_none());
+        assert!(TimeFilter::between(&ref_time, "2024-01-01").is_none());
+        assert!(TimeFilter::between(&ref_time, "2024-01-01..").is_none());
+    }
+
+    #[test]
+    fn reference_file() {
+        let dir = tempdir::TempDir::new("fd-time").unwrap();
+        let reference = dir.path().join("reference");
+        fs::write(&reference, "").unwrap();
+        let modified = fs::metadata(&reference).unwrap().modified().unwrap();
+
+        let newer = TimeFilter::newer_than(&reference, Timestamp::Modified).unwrap();
+        let older = TimeFilter::older_than(&reference, Timestamp::Modified).unwrap();
+        assert!(!newer.applies_to(&modified));
+        assert!(!older.applies_to(&modified));
+        assert!(newer.applies_to(&(modified + Duration::from_nanos(1))));
+        assert!(older.applies_to(&(modified - Duration::from_nanos(1))));
+
+        let newer = TimeFilter::newer_than(&reference, Timestamp::Accessed).unwrap();
+        assert_eq!(newer.timestamp(), Timestamp::Accessed);
+
+        assert!(TimeFilter::newer_than(&dir.path().join("missing"), Timestamp::Modified).is_err());
+    }
+
+    #[test]
+    fn select_timestamp() {
+        let ref_time = humantime::parse_rfc3339("2010-10-10T10:10:10Z").unwrap();
+        let filter = TimeFilter::after(&ref_time, "1min").unwrap();
+        assert_eq!(filter.timestamp(), Timestamp::Modified);
+
+        let filter = filter.of(Timestamp::Accessed);
+        assert_eq!(filter.timestamp(), Timestamp::Accessed);
+        assert!(filter.applies_to(&ref_time));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn inode_change_time() {
+        use std::os::unix::fs::MetadataExt;
+        use std::time::UNIX_EPOCH;
+
+        let metadata = std::fs::metadata(".").unwrap();
+        let ctime = Timestamp::InodeChanged.of(&metadata).unwrap();
+        let since_epoch = ctime.duration_since(UNIX_EPOCH).unwrap();
+        assert_eq!(since_epoch.as_secs() as i64, metadata.ctime());
+        assert_eq!(i64::from(since_epoch.subsec_nanos()), metadata.ctime_nsec());
+    }
 }
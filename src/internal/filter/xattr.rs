@@ -0,0 +1,145 @@
+use std::path::Path;
+
+use globset::{Glob, GlobMatcher};
+
+/// The extended attribute that holds the file capabilities on Linux.
+const CAPABILITY_ATTRIBUTE: &str = "security.capability";
+
+/// An extended attribute of a file: its name and value.
+pub type Xattr = (String, Vec<u8>);
+
+/// Filter on extended attributes, e.g. `--xattr user.origin` or
+/// `--xattr 'security.selinux=*:httpd_sys_content_t:*'`.
+///
+/// Both the name and the value are glob patterns. A file matches if it has at least one attribute
+/// whose name (and value, if given) matches.
+#[derive(Clone, Debug)]
+pub struct XattrFilter {
+    name: GlobMatcher,
+    value: Option<GlobMatcher>,
+}
+
+impl XattrFilter {
+    /// Parse `NAME` or `NAME=VALUE`.
+    pub fn from_string(s: &str) -> Option<XattrFilter> {
+        let (name, value) = match s.split_once('=') {
+            Some((name, value)) => (name, Some(value)),
+            None => (s, None),
+        };
+        if name.is_empty() {
+            return None;
+        }
+
+        let matcher = |pattern| Glob::new(pattern).ok().map(|g| g.compile_matcher());
+        Some(XattrFilter {
+            name: matcher(name)?,
+            value: match value {
+                Some(value) => Some(matcher(value)?),
+                None => None,
+            },
+        })
+    }
+
+    /// Match files with capabilities, i.e. a `security.capability` attribute.
+    pub fn capabilities() -> XattrFilter {
+        XattrFilter::from_string(CAPABILITY_ATTRIBUTE).unwrap()
+    }
+
+    /// The attributes of the file at `path` that match, or `None` if there are none (or they can
+    /// not be read). Symlinks are only followed if `follow` is set.
+    pub fn matching(&self, path: &Path, follow: bool) -> Option<Vec<Xattr>> {
+        let names = if follow {
+            xattr::list_deref(path)
+        } else {
+            xattr::list(path)
+        }
+        .ok()?;
+
+        let mut matched = vec![];
+        for name in names {
+            let name = name.to_string_lossy().into_owned();
+            if !self.name.is_match(&name) {
+                continue;
+            }
+
+            let value = if follow {
+                xattr::get_deref(path, &name)
+            } else {
+                xattr::get(path, &name)
+            };
+            let value = value.ok().flatten().unwrap_or_default();
+            if self.matches_value(&value) {
+                matched.push((name, value));
+            }
+        }
+
+        if matched.is_empty() {
+            None
+        } else {
+            Some(matched)
+        }
+    }
+
+    fn matches_value(&self, value: &[u8]) -> bool {
+        // Values that are strings (like SELinux labels) usually include the terminating NUL.
+        let value = value.strip_suffix(b"\0").unwrap_or(value);
+        match self.value {
+            Some(ref pattern) => pattern.is_match(&*String::from_utf8_lossy(value)),
+            None => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempdir::TempDir;
+
+    #[test]
+    fn parse_and_match_values() {
+        let label = XattrFilter::from_string("security.selinux=*:httpd_sys_content_t:*").unwrap();
+        assert!(label.name.is_match("security.selinux"));
+        assert!(label.matches_value(b"system_u:object_r:httpd_sys_content_t:s0\0"));
+        assert!(!label.matches_value(b"system_u:object_r:bin_t:s0\0"));
+
+        let user = XattrFilter::from_string("user.*").unwrap();
+        assert!(user.name.is_match("user.origin"));
+        assert!(!user.name.is_match("security.capability"));
+        assert!(user.matches_value(b"\x01\x02"));
+
+        let empty = XattrFilter::from_string("user.flag=").unwrap();
+        assert!(empty.matches_value(b""));
+        assert!(!empty.matches_value(b"1"));
+
+        assert!(XattrFilter::capabilities()
+            .name
+            .is_match("security.capability"));
+        assert!(XattrFilter::from_string("").is_none());
+        assert!(XattrFilter::from_string("=value").is_none());
+        assert!(XattrFilter::from_string("user.[").is_none());
+    }
+
+    #[test]
+    fn match_attributes_of_file() {
+        let dir = TempDir::new("fd-xattr").unwrap();
+        let path = dir.path().join("file");
+        std::fs::write(&path, "").unwrap();
+        if xattr::set(&path, "user.origin", b"upstream").is_err() {
+            // The file system does not support user attributes.
+            return;
+        }
+
+        let filter = XattrFilter::from_string("user.*=up*").unwrap();
+        assert_eq!(
+            filter.matching(&path, false),
+            Some(vec![("user.origin".to_string(), b"upstream".to_vec())])
+        );
+        assert_eq!(
+            XattrFilter::from_string("user.origin=down*")
+                .unwrap()
+                .matching(&path, false),
+            None
+        );
+        assert_eq!(XattrFilter::capabilities().matching(&path, false), None);
+    }
+}
@@ -1,13 +1,15 @@
-use crate::exec::CommandTemplate;
 use crate::internal::{
     filter::*,
     FileTypes,
 };
-use lscolors::LsColors;
 use regex::bytes::RegexSet;
-use std::{path::PathBuf, sync::Arc, time::Duration};
+use std::path::PathBuf;
 
 /// Configuration options for *fd*.
+///
+/// New options may be added at any time, so outside of this crate, the options can only be
+/// created with `FdOptions::default()` and then changed one by one (or through `Searcher`).
+#[non_exhaustive]
 pub struct FdOptions {
     /// Whether the search is case-sensitive or case-insensitive.
     pub case_sensitive: bool,
@@ -31,26 +33,20 @@ pub struct FdOptions {
     /// Whether to limit the search to starting file system or not.
     pub one_file_system: bool,
 
-    /// Whether elements of output should be separated by a null character
-    pub null_separator: bool,
-
     /// The maximum search depth, or `None` if no maximum search depth should be set.
     ///
     /// A depth of `1` includes all files under the current directory, a depth of `2` also includes
     /// all files under subdirectories of the current directory, etc.
     pub max_depth: Option<usize>,
 
-    /// The number of threads to use.
-    pub threads: usize,
+    /// The minimum depth for reported entries, or `None`.
+    pub min_depth: Option<usize>,
 
-    /// Time to buffer results internally before streaming to the console. This is useful to
-    /// provide a sorted output, in case the total execution time is shorter than
-    /// `max_buffer_time`.
-    pub max_buffer_time: Option<Duration>,
+    /// Whether to stop descending into directories that match the search.
+    pub prune: bool,
 
-    /// `None` if the output should not be colorized. Otherwise, a `LsColors` instance that defines
-    /// how to style different filetypes.
-    pub ls_colors: Option<LsColors>,
+    /// The number of threads to use.
+    pub threads: usize,
 
     /// The type of file to search for. If set to `None`, all file types are displayed. If
     /// set to `Some(..)`, only the types that are specified are shown.
@@ -61,9 +57,6 @@ pub struct FdOptions {
     /// The value (if present) will be a lowercase string without leading dots.
     pub extensions: Option<RegexSet>,
 
-    /// If a value is supplied, each item found will be used to generate and execute commands.
-    pub command: Option<Arc<CommandTemplate>>,
-
     /// A list of glob patterns that should be excluded from the search.
     pub exclude_patterns: Vec<String>,
 
@@ -73,6 +66,12 @@ pub struct FdOptions {
     /// The given constraints on the size of returned files
     pub size_constraints: Vec<SizeFilter>,
 
+    /// Whether to compute the total size of directories, so that size constraints apply to them
+    pub directory_sizes: bool,
+
+    /// Whether sizes are apparent or allocated sizes
+    pub size_kind: SizeKind,
+
     /// Constraints on last modification time of files
     pub time_constraints: Vec<TimeFilter>,
 
@@ -80,13 +79,115 @@ pub struct FdOptions {
     /// User/group ownership filters
     pub owner_filters: Vec<OwnerFilter>,
 
+    #[cfg(unix)]
+    /// Whether to only match files whose owner has no entry in the user database
+    pub no_user: bool,
+
+    #[cfg(unix)]
+    /// Whether to only match files whose group has no entry in the group database
+    pub no_group: bool,
+
     #[cfg(unix)]
     /// Permission mode filters
     pub permission_filters: Vec<PermissionFilter>,
 
-    /// Whether or not to display filesystem errors
-    pub show_filesystem_errors: bool,
+    #[cfg(unix)]
+    /// Filters on the number of hard links
+    pub links_filters: Vec<LinksFilter>,
+
+    #[cfg(unix)]
+    /// Filters on the inode (number or same file)
+    pub inode_filters: Vec<InodeFilter>,
+
+    #[cfg(unix)]
+    /// Whether to report hard links to the same inode only once
+    pub unique_inode: bool,
+
+    #[cfg(unix)]
+    /// Filters on whether the current (or a given) user may access the entries
+    pub access_filters: Vec<AccessFilter>,
+
+    #[cfg(unix)]
+    /// Filters on extended attributes
+    pub xattr_filters: Vec<XattrFilter>,
+
+    /// A boolean combination of filters that entries have to satisfy
+    pub filter_expression: Option<FilterExpr>,
+
+    /// Filters on the target of symlinks
+    pub link_target_filters: Vec<LinkTargetFilter>,
+
+    /// Patterns for the MIME type of files, of which one has to match
+    pub mime_filters: Vec<MimeFilter>,
+
+    /// Filters on the contents of files
+    pub content_filters: Vec<ContentFilter>,
+
+    /// The checksum to compute for every regular file, if any
+    pub checksum: Option<ChecksumKind>,
+
+    /// Filters on the checksum of files
+    pub checksum_filters: Vec<ChecksumFilter>,
+
+    /// Whether to only report regular files with identical contents, grouped by their contents
+    pub duplicates: bool,
+
+    /// The maximum number of entries to report, or `None`. The traversal stops once it is reached.
+    pub max_results: Option<usize>,
+}
 
-    /// The separator used to print file paths.
-    pub path_separator: Option<String>,
+impl Default for FdOptions {
+    /// The options *fd* uses when no command-line flags are given.
+    fn default() -> FdOptions {
+        FdOptions {
+            case_sensitive: false,
+            search_full_path: false,
+            ignore_hidden: true,
+            read_fdignore: true,
+            read_vcsignore: true,
+            follow_links: false,
+            one_file_system: false,
+            max_depth: None,
+            min_depth: None,
+            prune: false,
+            threads: num_cpus::get(),
+            file_types: None,
+            extensions: None,
+            exclude_patterns: vec![],
+            ignore_files: vec![],
+            size_constraints: vec![],
+            directory_sizes: false,
+            size_kind: SizeKind::Apparent,
+            time_constraints: vec![],
+
+            #[cfg(unix)]
+            owner_filters: vec![],
+            #[cfg(unix)]
+            no_user: false,
+            #[cfg(unix)]
+            no_group: false,
+            #[cfg(unix)]
+            permission_filters: vec![],
+            #[cfg(unix)]
+            links_filters: vec![],
+            #[cfg(unix)]
+            inode_filters: vec![],
+            #[cfg(unix)]
+            unique_inode: false,
+            #[cfg(unix)]
+            access_filters: vec![],
+            #[cfg(unix)]
+            xattr_filters: vec![],
+
+            filter_expression: None,
+            link_target_filters: vec![],
+            mime_filters: vec![],
+            content_filters: vec![],
+            checksum: None,
+            checksum_filters: vec![],
+
+            duplicates: false,
+            max_results: None,
+        }
+    }
 }
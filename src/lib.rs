@@ -0,0 +1,43 @@
+// Copyright (c) 2017 fd developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0>
+// or the MIT license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+//! The search engine behind *fd*, usable as a library.
+//!
+//! A search is configured through a [`Searcher`] and yields its results through a
+//! [`SearchResults`] iterator while the directory traversal runs on background threads:
+//!
+//! ```no_run
+//! use fd_find::{filter::SizeFilter, Searcher};
+//!
+//! let results = Searcher::new(r"\.rs$")
+//!     .root("src")
+//!     .hidden(true)
+//!     .size(SizeFilter::from_string("+1k").unwrap())
+//!     .search()?;
+//!
+//! for entry in results.filter_map(Result::ok) {
+//!     println!("{}", entry.path().display());
+//! }
+//! # Ok::<(), fd_find::Error>(())
+//! ```
+//!
+//! [`Searcher`]: struct.Searcher.html
+//! [`SearchResults`]: struct.SearchResults.html
+
+mod duplicates;
+mod error;
+pub mod fshelper;
+mod internal;
+mod search;
+mod walk;
+
+pub use crate::error::Error;
+pub use crate::internal::filter;
+pub use crate::internal::{opts::FdOptions, pattern_has_uppercase_char, FileTypes};
+pub use crate::search::{SearchResults, Searcher};
+pub use crate::walk::DirEntry;
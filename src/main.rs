@@ -6,18 +6,34 @@
 // notice may not be copied, modified, or distributed except
 // according to those terms.
 
-#[macro_use]
-mod internal;
+// The exit codes and `--exec` are older than these lints.
+#![allow(
+    clippy::from_over_into,
+    clippy::match_like_matches_macro,
+    clippy::needless_borrow
+)]
+#![cfg_attr(test, allow(clippy::needless_borrows_for_generic_args))]
+
+macro_rules! print_error {
+    ($($arg:tt)*) => (eprintln!("[fd error]: {}", format!($($arg)*)))
+}
+
+macro_rules! print_error_and_exit {
+    ($($arg:tt)*) => {
+        print_error!($($arg)*);
+        ::std::process::exit(1);
+    };
+}
 
 mod app;
+mod args;
 mod exec;
 mod exit_codes;
-pub mod fshelper;
 mod output;
-mod walk;
+mod receiver;
+mod sort;
 
 use std::env;
-use std::error::Error;
 use std::path::{Path, PathBuf};
 use std::process;
 use std::sync::Arc;
@@ -26,14 +42,13 @@ use std::time;
 use atty::Stream;
 use globset::Glob;
 use lscolors::LsColors;
-use regex::bytes::{RegexBuilder, RegexSetBuilder};
 
+use fd_find::{filter::*, fshelper, pattern_has_uppercase_char, FdOptions, FileTypes, Searcher};
+
+use crate::args::transform_args_with_exec;
 use crate::exec::CommandTemplate;
-use crate::internal::{
-    filter::*,
-    opts::FdOptions,
-    pattern_has_uppercase_char, transform_args_with_exec, FileTypes,
-};
+use crate::output::{FormatTemplate, OutputOptions};
+use crate::sort::SortKey;
 
 // We use jemalloc for performance reasons, see https://github.com/sharkdp/fd/pull/481
 #[cfg(all(not(windows), not(target_env = "musl")))]
@@ -179,29 +194,71 @@ fn main() {
             })
             .collect()
         })
-        .unwrap_or_else(|| vec![]);
+        .unwrap_or_default();
 
     let now = time::SystemTime::now();
     let mut time_constraints: Vec<TimeFilter> = Vec::new();
-    if let Some(t) = matches.value_of("changed-within") {
-        if let Some(f) = TimeFilter::after(&now, t) {
-            time_constraints.push(f);
+    let time_options = [
+        ("changed-within", "changed-before", Timestamp::Modified),
+        ("accessed-within", "accessed-before", Timestamp::Accessed),
+        (
+            "inode-changed-within",
+            "inode-changed-before",
+            Timestamp::InodeChanged,
+        ),
+        ("created-within", "created-before", Timestamp::Created),
+    ];
+    if let Some(t) = matches.value_of("changed-between") {
+        if let Some((start, end)) = TimeFilter::between(&now, t) {
+            time_constraints.push(start);
+            time_constraints.push(end);
         } else {
-            print_error_and_exit!("'{}' is not a valid date or duration. See 'fd --help'.", t);
+            print_error_and_exit!(
+                "'{}' is not a valid time interval (START..END). See 'fd --help'.",
+                t
+            );
         }
     }
-    if let Some(t) = matches.value_of("changed-before") {
-        if let Some(f) = TimeFilter::before(&now, t) {
-            time_constraints.push(f);
-        } else {
-            print_error_and_exit!("'{}' is not a valid date or duration. See 'fd --help'.", t);
+    let reference_timestamp = match matches.value_of("compare-time") {
+        Some("atime") => Timestamp::Accessed,
+        Some("ctime") => Timestamp::InodeChanged,
+        Some("btime") => Timestamp::Created,
+        _ => Timestamp::Modified,
+    };
+    for (name, new_filter) in &[
+        ("newer", TimeFilter::newer_than as fn(&Path, Timestamp) -> _),
+        ("older", TimeFilter::older_than),
+    ] {
+        if let Some(path) = matches.value_of(name) {
+            match new_filter(Path::new(path), reference_timestamp) {
+                Ok(f) => time_constraints.push(f),
+                Err(e) => {
+                    print_error_and_exit!("Could not access '{}': {}.", path, e);
+                }
+            }
+        }
+    }
+    for &(within, before, timestamp) in &time_options {
+        if let Some(t) = matches.value_of(within) {
+            if let Some(f) = TimeFilter::after(&now, t) {
+                time_constraints.push(f.of(timestamp));
+            } else {
+                print_error_and_exit!("'{}' is not a valid date or duration. See 'fd --help'.", t);
+            }
+        }
+        if let Some(t) = matches.value_of(before) {
+            if let Some(f) = TimeFilter::before(&now, t) {
+                time_constraints.push(f.of(timestamp));
+            } else {
+                print_error_and_exit!("'{}' is not a valid date or duration. See 'fd --help'.", t);
+            }
         }
     }
 
     #[cfg(unix)]
     let owners: Vec<OwnerFilter> = matches
         .values_of("owner")
-        .map_or_else(|| vec![], |v| {
+        .map_or_else(std::vec::Vec::new, |v| {
             v.map(|o| {
                 if let Some(f) = OwnerFilter::from_string(o) {
                     return f;
@@ -214,7 +271,7 @@ fn main() {
     #[cfg(unix)]
     let perm_modes: Vec<PermissionFilter> = matches
         .values_of("permission")
-        .map_or_else(|| vec![], |v| {
+        .map_or_else(std::vec::Vec::new, |v| {
             v.map(|p| {
                 if let Some(f) = PermissionFilter::from_string(p) {
                     return f;
@@ -224,111 +281,311 @@ fn main() {
             .collect()
         });
 
-    let config = FdOptions {
-        case_sensitive,
-        search_full_path: matches.is_present("full-path"),
-        ignore_hidden: !(matches.is_present("hidden")
-            || matches.occurrences_of("rg-alias-hidden-ignore") >= 2),
-        read_fdignore: !(matches.is_present("no-ignore")
-            || matches.is_present("rg-alias-hidden-ignore")),
-        read_vcsignore: !(matches.is_present("no-ignore")
-            || matches.is_present("rg-alias-hidden-ignore")
-            || matches.is_present("no-ignore-vcs")),
-        follow_links: matches.is_present("follow"),
-        one_file_system: matches.is_present("one-file-system"),
-        null_separator: matches.is_present("null_separator"),
-        max_depth: matches
-            .value_of("depth")
-            .or_else(|| matches.value_of("rg-depth"))
-            .and_then(|n| usize::from_str_radix(n, 10).ok()),
-        threads: std::cmp::max(
-            matches
-                .value_of("threads")
-                .and_then(|n| usize::from_str_radix(n, 10).ok())
-                .unwrap_or_else(num_cpus::get),
-            1,
-        ),
-        max_buffer_time: matches
-            .value_of("max-buffer-time")
-            .and_then(|n| u64::from_str_radix(n, 10).ok())
-            .map(time::Duration::from_millis),
-        ls_colors,
-        file_types: matches.values_of("file-type").map(|values| {
-            let mut file_types = FileTypes::default();
-            for value in values {
-                match value {
-                    "f" | "file" => file_types.files = true,
-                    "d" | "directory" => file_types.directories = true,
-                    "l" | "symlink" => file_types.symlinks = true,
-                    "x" | "executable" => {
-                        file_types.executables_only = true;
-                        file_types.files = true;
-                    }
-                    "e" | "empty" => {
-                        file_types.empty_only = true;
-                    }
-                    _ => unreachable!(),
-                }
+    #[cfg(unix)]
+    let links_filters: Vec<LinksFilter> = matches.values_of("links").map_or_else(Vec::new, |v| {
+        v.map(|l| {
+            if let Some(f) = LinksFilter::from_string(l) {
+                return f;
             }
+            print_error_and_exit!("'{}' is not a valid number of links. See 'fd --help'.", l);
+        })
+        .collect()
+    });
 
-            // If only 'empty' was specified, search for both files and directories:
-            if file_types.empty_only && !(file_types.files || file_types.directories) {
-                file_types.files = true;
-                file_types.directories = true;
+    #[cfg(unix)]
+    let mut inode_filters: Vec<InodeFilter> = Vec::new();
+    #[cfg(unix)]
+    {
+        if let Some(n) = matches.value_of("inum") {
+            if let Ok(n) = n.parse() {
+                inode_filters.push(InodeFilter::Number(n));
+            } else {
+                print_error_and_exit!("'{}' is not a valid inode number. See 'fd --help'.", n);
             }
+        }
+        if let Some(path) = matches.value_of("samefile") {
+            match InodeFilter::same_file(Path::new(path)) {
+                Ok(f) => inode_filters.push(f),
+                Err(e) => {
+                    print_error_and_exit!("Could not access '{}': {}.", path, e);
+                }
+            }
+        }
+    }
 
-            file_types
-        }),
-        extensions: matches.values_of("extension").map(|exts| {
-            let patterns = exts
-                .map(|e| e.trim_start_matches('.'))
-                .map(|e| format!(r".\.{}$", regex::escape(e)));
-            match RegexSetBuilder::new(patterns)
-                .case_insensitive(true)
-                .build()
-            {
-                Ok(re) => re,
-                Err(err) => {
-                    print_error_and_exit!("{}", err.description());
+    #[cfg(unix)]
+    let mut xattr_filters: Vec<XattrFilter> =
+        matches.values_of("xattr").map_or_else(Vec::new, |v| {
+            v.map(|x| {
+                if let Some(f) = XattrFilter::from_string(x) {
+                    return f;
                 }
+                print_error_and_exit!(
+                    "'{}' is not a valid extended attribute filter. See 'fd --help'.",
+                    x
+                );
+            })
+            .collect()
+        });
+    #[cfg(unix)]
+    {
+        if matches.is_present("has-capabilities") {
+            xattr_filters.push(XattrFilter::capabilities());
+        }
+    }
+
+    let filter_expression = matches.values_of("where").and_then(|v| {
+        v.map(|e| {
+            FilterExpr::from_string(e, &now).unwrap_or_else(|err| {
+                print_error_and_exit!(
+                    "'{}' is not a valid filter expression: {}. See 'fd --help'.",
+                    e,
+                    err
+                );
+            })
+        })
+        .reduce(|lhs, rhs| FilterExpr::And(Box::new(lhs), Box::new(rhs)))
+    });
+
+    let link_target_filters: Vec<LinkTargetFilter> =
+        matches.values_of("link-target").map_or_else(Vec::new, |v| {
+            v.map(|pattern| {
+                LinkTargetFilter::new(pattern).unwrap_or_else(|err| {
+                    print_error_and_exit!(
+                        "'{}' is not a valid regular expression: {}. See 'fd --help'.",
+                        pattern,
+                        err
+                    );
+                })
+            })
+            .collect()
+        });
+
+    #[cfg(unix)]
+    let access_filters: Vec<AccessFilter> = {
+        let user = matches.value_of("access-user").map(|name| {
+            AccessUser::from_string(name).unwrap_or_else(|| {
+                print_error_and_exit!("'{}' is not a known user. See 'fd --help'.", name);
+            })
+        });
+        let filters: Vec<_> = [
+            ("readable", Access::Read),
+            ("writable", Access::Write),
+            ("executable-by-me", Access::Execute),
+        ]
+        .iter()
+        .filter(|(name, _)| matches.is_present(name))
+        .map(|&(_, access)| match user {
+            Some(ref user) => AccessFilter::for_user(access, user.clone()),
+            None => AccessFilter::current_user(access),
+        })
+        .collect();
+        if user.is_some() && filters.is_empty() {
+            print_error_and_exit!(
+                "'--access-user' requires '--readable', '--writable' or '--executable-by-me'."
+            );
+        }
+        filters
+    };
+
+    let mime_filters: Vec<MimeFilter> = matches.values_of("mime").map_or_else(Vec::new, |v| {
+        v.map(|m| {
+            if let Some(f) = MimeFilter::from_string(m) {
+                return f;
             }
-        }),
-        command: command.map(Arc::new),
-        exclude_patterns: matches
-            .values_of("exclude")
-            .map(|v| v.map(|p| String::from("!") + p).collect())
-            .unwrap_or_else(|| vec![]),
-        ignore_files: matches
-            .values_of("ignore-file")
-            .map(|vs| vs.map(PathBuf::from).collect())
-            .unwrap_or_else(|| vec![]),
-        size_constraints: size_limits,
-        time_constraints,
-
-        #[cfg(unix)]
-        owner_filters: owners,
-        #[cfg(unix)]
-        permission_filters: perm_modes,
+            print_error_and_exit!("'{}' is not a valid MIME type pattern. See 'fd --help'.", m);
+        })
+        .collect()
+    });
+
+    let mut content_filters = Vec::new();
+    for (name, new_filter) in &[
+        ("contains", ContentFilter::contains as fn(&str) -> _),
+        ("not-contains", ContentFilter::not_contains),
+    ] {
+        for pattern in matches.values_of(name).into_iter().flatten() {
+            content_filters.push(new_filter(pattern).unwrap_or_else(|err| {
+                print_error_and_exit!(
+                    "'{}' is not a valid regular expression: {}. See 'fd --help'.",
+                    pattern,
+                    err
+                );
+            }));
+        }
+    }
+
+    let checksum = matches
+        .value_of("checksum")
+        .and_then(ChecksumKind::from_name);
+    let checksum_filters: Vec<ChecksumFilter> = matches
+        .value_of("checksum-matches")
+        .map(|hex| {
+            let filter = match checksum {
+                Some(kind) => ChecksumFilter::new(kind, hex),
+                None => ChecksumFilter::guess(hex),
+            };
+            filter.unwrap_or_else(|| {
+                print_error_and_exit!(
+                    "'{}' is not a valid {} checksum. See 'fd --help'.",
+                    hex,
+                    checksum.map_or("MD5 or SHA-256", ChecksumKind::name)
+                );
+            })
+        })
+        .into_iter()
+        .collect();
+
+    let sort = matches
+        .value_of("sort")
+        .and_then(SortKey::from_string)
+        .or_else(|| {
+            (matches.is_present("reverse") || matches.is_present("list-details"))
+                .then_some(SortKey::Path)
+        });
+
+    // An invalid limit must not be taken as no limit at all.
+    let parse_limit = |name: &str| -> Option<usize> {
+        matches.value_of(name).map(|n| {
+            n.parse().unwrap_or_else(|_| {
+                print_error_and_exit!(
+                    "'{}' is not a valid value for '--{}'. See 'fd --help'.",
+                    n,
+                    name
+                );
+            })
+        })
+    };
+
+    let max_results =
+        parse_limit("max-results").or_else(|| matches.is_present("max-one-result").then_some(1));
+    let exact_depth = parse_limit("exact-depth");
+
+    let duplicates = matches.is_present("duplicates");
 
+    let threads = std::cmp::max(
+        matches
+            .value_of("threads")
+            .and_then(|n| n.parse().ok())
+            .unwrap_or_else(num_cpus::get),
+        1,
+    );
+
+    let mut config = FdOptions::default();
+    config.case_sensitive = case_sensitive;
+    config.search_full_path = matches.is_present("full-path");
+    config.ignore_hidden =
+        !(matches.is_present("hidden") || matches.occurrences_of("rg-alias-hidden-ignore") >= 2);
+    config.read_fdignore =
+        !(matches.is_present("no-ignore") || matches.is_present("rg-alias-hidden-ignore"));
+    config.read_vcsignore = !(matches.is_present("no-ignore")
+        || matches.is_present("rg-alias-hidden-ignore")
+        || matches.is_present("no-ignore-vcs"));
+    config.follow_links = matches.is_present("follow");
+    config.one_file_system = matches.is_present("one-file-system");
+    config.max_depth = matches
+        .value_of("depth")
+        .or_else(|| matches.value_of("rg-depth"))
+        .and_then(|n| n.parse().ok())
+        .or(exact_depth);
+    config.min_depth = parse_limit("min-depth").or(exact_depth);
+    config.prune = matches.is_present("prune");
+    config.threads = threads;
+    config.file_types = matches
+        .values_of("file-type")
+        .map(|values| FileTypes::from_names(values).unwrap());
+    config.exclude_patterns = matches
+        .values_of("exclude")
+        .map(|v| v.map(|p| String::from("!") + p).collect())
+        .unwrap_or_default();
+    config.ignore_files = matches
+        .values_of("ignore-file")
+        .map(|vs| vs.map(PathBuf::from).collect())
+        .unwrap_or_default();
+    config.size_constraints = size_limits;
+    config.directory_sizes = matches.is_present("dir-size");
+    config.size_kind = if matches.is_present("allocated-size") {
+        SizeKind::Allocated
+    } else {
+        SizeKind::Apparent
+    };
+    config.time_constraints = time_constraints;
+
+    #[cfg(unix)]
+    {
+        config.owner_filters = owners;
+        config.no_user = matches.is_present("nouser");
+        config.no_group = matches.is_present("nogroup");
+        config.permission_filters = perm_modes;
+        config.links_filters = links_filters;
+        config.inode_filters = inode_filters;
+        config.unique_inode = matches.is_present("unique-inode");
+        config.access_filters = access_filters;
+        config.xattr_filters = xattr_filters;
+    }
+
+    config.filter_expression = filter_expression;
+    config.link_target_filters = link_target_filters;
+    config.mime_filters = mime_filters;
+    config.content_filters = content_filters;
+    config.checksum = checksum;
+    config.checksum_filters = checksum_filters;
+
+    config.duplicates = duplicates;
+    config.max_results = if sort.is_some() || duplicates {
+        None
+    } else {
+        max_results
+    };
+
+    let output_config = OutputOptions {
+        null_separator: matches.is_present("null_separator"),
+        max_buffer_time: matches
+            .value_of("max-buffer-time")
+            .and_then(|n| n.parse().ok())
+            .map(time::Duration::from_millis),
+        sort,
+        reverse: matches.is_present("reverse"),
+        ls_colors,
+        command: command.map(Arc::new),
         show_filesystem_errors: matches.is_present("show-errors"),
         path_separator,
+        json: matches.is_present("json"),
+        checksum,
+        duplicates,
+        list_details: matches.is_present("list-details"),
+        show_link_target: matches.is_present("show-link-target"),
+        format: matches.value_of("format").map(|f| {
+            FormatTemplate::parse(f).unwrap_or_else(|err| {
+                print_error_and_exit!("'{}' is not a valid format string: {}.", f, err);
+            })
+        }),
+        // Sorted output and groups of duplicates can only be limited once all results are known.
+        max_results: if sort.is_some() || duplicates {
+            max_results
+        } else {
+            None
+        },
     };
 
-    match RegexBuilder::new(&pattern_regex)
-        .case_insensitive(!config.case_sensitive)
-        .dot_matches_new_line(true)
-        .build()
-    {
-        Ok(re) => {
-            let exit_code = walk::scan(&dir_vec, Arc::new(re), Arc::new(config));
+    let searcher = Searcher::new(pattern_regex)
+        .options(config)
+        .roots(dir_vec)
+        .extensions(matches.values_of("extension").into_iter().flatten());
+
+    match searcher.search() {
+        Ok(results) => {
+            let exit_code = receiver::scan(results, Arc::new(output_config), threads);
             process::exit(exit_code.into());
         }
-        Err(err) => {
+        Err(fd_find::Error::InvalidPattern(err)) => {
             print_error_and_exit!(
                 "{}\nHint: You can use the '--fixed-strings' option to search for a \
                  literal string instead of a regular expression",
-                err.description()
+                err
             );
         }
+        Err(err) => {
+            print_error_and_exit!("{}", err);
+        }
     }
 }
@@ -0,0 +1,198 @@
+// Copyright (c) 2017 fd developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0>
+// or the MIT license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use std::fs::Metadata;
+use std::io::{self, StdoutLock, Write};
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use chrono::{DateTime, Local};
+
+#[cfg(unix)]
+use fd_find::fshelper;
+use fd_find::DirEntry;
+
+use super::OutputOptions;
+
+/// Modification times older than this (or in the future) are shown with the year instead of the
+/// time of day, like `ls -l` does.
+const RECENT: Duration = Duration::from_secs(60 * 60 * 24 * 365 / 2);
+
+/// The columns of a long listing, except for the path.
+struct Row {
+    permissions: String,
+    owner: String,
+    group: String,
+    size: String,
+    modified: String,
+}
+
+/// Print the entries in the format of `ls -l`: permissions, owner, group, size and modification
+/// time in aligned columns, followed by the (colored) path.
+pub fn print_entries(
+    stdout: &mut StdoutLock,
+    entries: &[DirEntry],
+    config: &OutputOptions,
+    wants_to_quit: &Arc<AtomicBool>,
+) -> io::Result<()> {
+    let now = SystemTime::now();
+    let rows: Vec<_> = entries.iter().map(|entry| Row::new(entry, now)).collect();
+
+    let width = |column: fn(&Row) -> &String| {
+        rows.iter()
+            .map(|row| column(row).chars().count())
+            .max()
+            .unwrap_or(0)
+    };
+    let owner_width = width(|row| &row.owner);
+    let group_width = width(|row| &row.group);
+    let size_width = width(|row| &row.size);
+    let modified_width = width(|row| &row.modified);
+
+    for (entry, row) in entries.iter().zip(&rows) {
+        write!(
+            stdout,
+            "{} {:<ow$} {:<gw$} {:>sw$} {:<mw$} ",
+            row.permissions,
+            row.owner,
+            row.group,
+            row.size,
+            row.modified,
+            ow = owner_width,
+            gw = group_width,
+            sw = size_width,
+            mw = modified_width,
+        )?;
+        super::print_entry(stdout, entry, config, wants_to_quit);
+    }
+
+    Ok(())
+}
+
+impl Row {
+    fn new(entry: &DirEntry, now: SystemTime) -> Row {
+        let metadata = match entry.metadata() {
+            Some(metadata) => metadata,
+            None => {
+                return Row {
+                    permissions: "?".repeat(10),
+                    owner: "?".to_string(),
+                    group: "?".to_string(),
+                    size: "?".to_string(),
+                    modified: "?".to_string(),
+                }
+            }
+        };
+
+        let (owner, group) = owner_and_group(metadata);
+        Row {
+            permissions: permissions(metadata),
+            owner,
+            group,
+            size: entry
+                .size()
+                .map_or_else(|| "?".to_string(), human_readable_size),
+            modified: metadata
+                .modified()
+                .map(|t| format_modified(t, now))
+                .unwrap_or_else(|_| "?".to_string()),
+        }
+    }
+}
+
+#[cfg(unix)]
+fn permissions(metadata: &Metadata) -> String {
+    fshelper::mode_string(metadata)
+}
+
+#[cfg(not(unix))]
+fn permissions(metadata: &Metadata) -> String {
+    let file_type = metadata.file_type();
+    let type_char = if file_type.is_dir() {
+        'd'
+    } else if file_type.is_symlink() {
+        'l'
+    } else {
+        '-'
+    };
+    let write = if metadata.permissions().readonly() {
+        '-'
+    } else {
+        'w'
+    };
+    format!("{}r{}-r{}-r{}-", type_char, write, write, write)
+}
+
+/// The owner and group names, or their ids if they have no names. The names are cached by
+/// `fshelper`.
+#[cfg(unix)]
+fn owner_and_group(metadata: &Metadata) -> (String, String) {
+    let uid = fshelper::get_uid(metadata);
+    let gid = fshelper::get_gid(metadata);
+    (
+        fshelper::get_user_name(uid).unwrap_or_else(|| uid.to_string()),
+        fshelper::get_group_name(gid).unwrap_or_else(|| gid.to_string()),
+    )
+}
+
+#[cfg(not(unix))]
+fn owner_and_group(_: &Metadata) -> (String, String) {
+    ("-".to_string(), "-".to_string())
+}
+
+/// Format a size like `ls -lh`, e.g. `999`, `1.5K` or `12M`.
+fn human_readable_size(size: u64) -> String {
+    const UNITS: [char; 6] = ['K', 'M', 'G', 'T', 'P', 'E'];
+
+    if size < 1024 {
+        return size.to_string();
+    }
+
+    let mut value = size as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+
+    if value < 10.0 {
+        format!("{:.1}{}", value, UNITS[unit])
+    } else {
+        format!("{:.0}{}", value, UNITS[unit])
+    }
+}
+
+fn format_modified(modified: SystemTime, now: SystemTime) -> String {
+    let recent = modified <= now && now.duration_since(modified).is_ok_and(|age| age < RECENT);
+    let format = if recent { "%b %e %H:%M" } else { "%b %e  %Y" };
+    DateTime::<Local>::from(modified).format(format).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn human_readable_sizes() {
+        assert_eq!(human_readable_size(0), "0");
+        assert_eq!(human_readable_size(1023), "1023");
+        assert_eq!(human_readable_size(1024), "1.0K");
+        assert_eq!(human_readable_size(1536), "1.5K");
+        assert_eq!(human_readable_size(20 * 1024), "20K");
+        assert_eq!(human_readable_size(5 * 1024 * 1024 * 1024), "5.0G");
+    }
+
+    #[test]
+    fn recent_and_old_modification_times() {
+        let now = SystemTime::now();
+        let old = now - Duration::from_secs(60 * 60 * 24 * 365);
+        assert!(format_modified(now, now).contains(':'));
+        assert!(!format_modified(old, now).contains(':'));
+    }
+}
@@ -0,0 +1,319 @@
+// Copyright (c) 2017 fd developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0>
+// or the MIT license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use std::fs::Metadata;
+use std::io::{self, Write};
+use std::path::Path;
+use std::time::SystemTime;
+
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, Local};
+
+use fd_find::DirEntry;
+
+use super::{json, replace_path_separator, OutputOptions};
+use crate::exec::{basename, dirname, remove_extension, Token};
+
+/// The time format that is used if none is given, e.g. `{mtime}`.
+const DEFAULT_TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// A metadata field that can be used in a format string.
+#[derive(Clone, Debug, PartialEq)]
+enum Field {
+    Size,
+    Mode,
+    Permissions,
+    Owner,
+    Group,
+    Uid,
+    Gid,
+    Depth,
+    Type,
+    Checksum,
+    Mime,
+    LinkTarget,
+    ModificationTime(String),
+    AccessTime(String),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Part {
+    Token(Token),
+    Field(Field),
+}
+
+/// A user-defined output format, e.g. `{path}\t{size}\t{mtime:%Y-%m-%d}`.
+///
+/// The path placeholders are the ones of `--exec` (`{}`, `{/}`, `{//}`, `{.}`, `{/.}`, with
+/// `{path}` as an alias for `{}`). Metadata fields are written as `{name}` or `{name:spec}`.
+/// Literal braces are written as `{{` and `}}`, and `\t`, `\n` and `\\` are unescaped.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FormatTemplate {
+    parts: Vec<Part>,
+}
+
+impl FormatTemplate {
+    pub fn parse(s: &str) -> Result<FormatTemplate, String> {
+        let mut parts = vec![];
+        let mut text = String::new();
+        let mut chars = s.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    text.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    text.push('}');
+                }
+                '{' => {
+                    let mut placeholder = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => placeholder.push(c),
+                            None => {
+                                return Err(format!("unterminated placeholder '{{{}'", placeholder))
+                            }
+                        }
+                    }
+
+                    if !text.is_empty() {
+                        parts.push(Part::Token(Token::Text(std::mem::take(&mut text))));
+                    }
+                    parts.push(parse_placeholder(&placeholder)?);
+                }
+                '}' => return Err("unmatched '}', use '}}' for a literal brace".to_string()),
+                '\\' => match chars.peek() {
+                    Some('t') => {
+                        chars.next();
+                        text.push('\t');
+                    }
+                    Some('n') => {
+                        chars.next();
+                        text.push('\n');
+                    }
+                    Some('\\') => {
+                        chars.next();
+                        text.push('\\');
+                    }
+                    _ => text.push('\\'),
+                },
+                c => text.push(c),
+            }
+        }
+
+        if !text.is_empty() {
+            parts.push(Part::Token(Token::Text(text)));
+        }
+
+        Ok(FormatTemplate { parts })
+    }
+
+    /// Write the formatted entry, without a terminating newline.
+    pub fn print_entry<W: Write>(
+        &self,
+        stdout: &mut W,
+        entry: &DirEntry,
+        path: &Path,
+        config: &OutputOptions,
+    ) -> io::Result<()> {
+        let mut path_str = path.to_string_lossy();
+        replace_path_separator(config, &mut path_str);
+        let metadata = entry.metadata();
+
+        for part in &self.parts {
+            match part {
+                Part::Token(Token::Placeholder) => write!(stdout, "{}", path_str)?,
+                Part::Token(Token::Basename) => write!(stdout, "{}", basename(&path_str))?,
+                Part::Token(Token::Parent) => write!(stdout, "{}", dirname(&path_str))?,
+                Part::Token(Token::NoExt) => write!(stdout, "{}", remove_extension(&path_str))?,
+                Part::Token(Token::BasenameNoExt) => {
+                    write!(stdout, "{}", remove_extension(basename(&path_str)))?
+                }
+                Part::Token(Token::Text(text)) => write!(stdout, "{}", text)?,
+                Part::Field(field) => write!(
+                    stdout,
+                    "{}",
+                    render_field(field, entry, metadata)
+                        .as_deref()
+                        .unwrap_or("?")
+                )?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn parse_placeholder(placeholder: &str) -> Result<Part, String> {
+    let token = match placeholder {
+        "" | "path" => Some(Token::Placeholder),
+        "/" => Some(Token::Basename),
+        "//" => Some(Token::Parent),
+        "." => Some(Token::NoExt),
+        "/." => Some(Token::BasenameNoExt),
+        _ => None,
+    };
+    if let Some(token) = token {
+        return Ok(Part::Token(token));
+    }
+
+    let (name, spec) = match placeholder.find(':') {
+        Some(i) => (&placeholder[..i], Some(&placeholder[i + 1..])),
+        None => (placeholder, None),
+    };
+
+    let time_format = || -> Result<String, String> {
+        let spec = spec.unwrap_or(DEFAULT_TIME_FORMAT);
+        if StrftimeItems::new(spec).any(|item| item == Item::Error) {
+            return Err(format!("invalid time format '{}'", spec));
+        }
+        Ok(spec.to_string())
+    };
+
+    let field = match name {
+        "mtime" => Field::ModificationTime(time_format()?),
+        "atime" => Field::AccessTime(time_format()?),
+        _ if spec.is_some() => {
+            return Err(format!(
+                "the placeholder '{{{}}}' does not take a format",
+                name
+            ))
+        }
+        "size" => Field::Size,
+        "mode" => Field::Mode,
+        "perm" | "permissions" => Field::Permissions,
+        "owner" | "user" => Field::Owner,
+        "group" => Field::Group,
+        "uid" => Field::Uid,
+        "gid" => Field::Gid,
+        "depth" => Field::Depth,
+        "type" => Field::Type,
+        "checksum" => Field::Checksum,
+        "mime" => Field::Mime,
+        "target" => Field::LinkTarget,
+        _ => return Err(format!("unknown placeholder '{{{}}}'", placeholder)),
+    };
+
+    Ok(Part::Field(field))
+}
+
+fn render_field(field: &Field, entry: &DirEntry, metadata: Option<&Metadata>) -> Option<String> {
+    match field {
+        Field::Size => entry.size().map(|size| size.to_string()),
+        Field::Depth => Some(entry.depth().to_string()),
+        Field::Type => entry
+            .file_type()
+            .map(|t| json::file_type_name(t).to_string()),
+        Field::Checksum => entry.checksum().map(str::to_string),
+        Field::Mime => entry.mime_type().map(str::to_string),
+        Field::LinkTarget => entry
+            .link_target()
+            .map(|target| target.to_string_lossy().into_owned()),
+        Field::ModificationTime(spec) => metadata
+            .and_then(|m| m.modified().ok())
+            .map(|t| format_time(t, spec)),
+        Field::AccessTime(spec) => metadata
+            .and_then(|m| m.accessed().ok())
+            .map(|t| format_time(t, spec)),
+        #[cfg(unix)]
+        Field::Mode => {
+            use std::os::unix::fs::PermissionsExt;
+            metadata.map(|m| format!("{:04o}", m.permissions().mode() & 0o7777))
+        }
+        #[cfg(unix)]
+        Field::Permissions => metadata.map(fd_find::fshelper::mode_string),
+        #[cfg(unix)]
+        Field::Owner => metadata.map(|m| {
+            let uid = fd_find::fshelper::get_uid(m);
+            fd_find::fshelper::get_user_name(uid).unwrap_or_else(|| uid.to_string())
+        }),
+        #[cfg(unix)]
+        Field::Group => metadata.map(|m| {
+            let gid = fd_find::fshelper::get_gid(m);
+            fd_find::fshelper::get_group_name(gid).unwrap_or_else(|| gid.to_string())
+        }),
+        #[cfg(unix)]
+        Field::Uid => metadata.map(|m| fd_find::fshelper::get_uid(m).to_string()),
+        #[cfg(unix)]
+        Field::Gid => metadata.map(|m| fd_find::fshelper::get_gid(m).to_string()),
+        #[cfg(not(unix))]
+        Field::Mode
+        | Field::Permissions
+        | Field::Owner
+        | Field::Group
+        | Field::Uid
+        | Field::Gid => None,
+    }
+}
+
+fn format_time(time: SystemTime, spec: &str) -> String {
+    DateTime::<Local>::from(time).format(spec).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(s: &str) -> Part {
+        Part::Token(Token::Text(s.to_string()))
+    }
+
+    #[test]
+    fn parse_tokens_and_fields() {
+        assert_eq!(
+            FormatTemplate::parse(r"{path}\t{size}\t{mtime:%Y-%m-%d}"),
+            Ok(FormatTemplate {
+                parts: vec![
+                    Part::Token(Token::Placeholder),
+                    text("\t"),
+                    Part::Field(Field::Size),
+                    text("\t"),
+                    Part::Field(Field::ModificationTime("%Y-%m-%d".to_string())),
+                ]
+            })
+        );
+
+        assert_eq!(
+            FormatTemplate::parse("{//}/{/.}.bak {} {atime}"),
+            Ok(FormatTemplate {
+                parts: vec![
+                    Part::Token(Token::Parent),
+                    text("/"),
+                    Part::Token(Token::BasenameNoExt),
+                    text(".bak "),
+                    Part::Token(Token::Placeholder),
+                    text(" "),
+                    Part::Field(Field::AccessTime(DEFAULT_TIME_FORMAT.to_string())),
+                ]
+            })
+        );
+    }
+
+    #[test]
+    fn parse_escapes() {
+        assert_eq!(
+            FormatTemplate::parse(r"{{{depth}}} a\\b\x"),
+            Ok(FormatTemplate {
+                parts: vec![text("{"), Part::Field(Field::Depth), text(r"} a\b\x"),]
+            })
+        );
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert!(FormatTemplate::parse("{path").is_err());
+        assert!(FormatTemplate::parse("path}").is_err());
+        assert!(FormatTemplate::parse("{colour}").is_err());
+        assert!(FormatTemplate::parse("{size:h}").is_err());
+        assert!(FormatTemplate::parse("{mtime:%Q}").is_err());
+    }
+}
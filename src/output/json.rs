@@ -0,0 +1,276 @@
+// Copyright (c) 2017 fd developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0>
+// or the MIT license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use std::fs::{FileType, Metadata};
+use std::io::{self, Write};
+use std::path::Path;
+use std::time::UNIX_EPOCH;
+
+use fd_find::DirEntry;
+
+use super::{replace_path_separator, OutputOptions};
+
+/// Print an entry as a single line of JSON.
+pub fn print_entry<W: Write>(
+    stdout: &mut W,
+    entry: &DirEntry,
+    path: &Path,
+    config: &OutputOptions,
+) -> io::Result<()> {
+    write_entry(stdout, entry, path, config)?;
+    writeln!(stdout)
+}
+
+/// Print a group of files with identical contents as a single line of JSON, with the size of
+/// each file, the number of bytes wasted on copies and the files themselves.
+pub fn print_group<W: Write>(
+    stdout: &mut W,
+    entries: &[DirEntry],
+    size: u64,
+    wasted: u64,
+    config: &OutputOptions,
+) -> io::Result<()> {
+    write!(
+        stdout,
+        "{{\"size\":{},\"wasted\":{},\"files\":[",
+        size, wasted
+    )?;
+    for (i, entry) in entries.iter().enumerate() {
+        if i > 0 {
+            write!(stdout, ",")?;
+        }
+        write_entry(stdout, entry, super::display_path(entry), config)?;
+    }
+    writeln!(stdout, "]}}")
+}
+
+/// Write an entry as a JSON object.
+///
+/// The path is given as `"path"` if it is valid UTF-8. Otherwise, its raw bytes are given as
+/// `"path_base64"`. Fields that can not be determined (for example if the metadata can not be
+/// read) are `null`. Symlinks also have their `"target"` (or `"target_base64"`).
+fn write_entry<W: Write>(
+    stdout: &mut W,
+    entry: &DirEntry,
+    path: &Path,
+    config: &OutputOptions,
+) -> io::Result<()> {
+    let metadata = entry.metadata();
+
+    write!(stdout, "{{")?;
+    match path.to_str() {
+        Some(path_str) => {
+            let mut path_str = path_str.into();
+            replace_path_separator(config, &mut path_str);
+            write!(stdout, "\"path\":{}", escape(&path_str))?;
+        }
+        None => write!(stdout, "\"path_base64\":\"{}\"", base64(&path_bytes(path)))?,
+    }
+
+    write!(
+        stdout,
+        ",\"type\":{}",
+        optional(entry.file_type().map(|t| escape(file_type_name(t))))
+    )?;
+    write!(stdout, ",\"size\":{}", optional(entry.size()))?;
+    write!(stdout, ",\"mode\":{}", optional(mode(metadata)))?;
+    write!(stdout, ",\"uid\":{}", optional(uid(metadata)))?;
+    write!(stdout, ",\"gid\":{}", optional(gid(metadata)))?;
+    let mtime = metadata
+        .and_then(|m| m.modified().ok())
+        .filter(|t| *t >= UNIX_EPOCH)
+        .map(|t| format!("\"{}\"", humantime::format_rfc3339_seconds(t)));
+    write!(stdout, ",\"mtime\":{}", optional(mtime))?;
+    write!(stdout, ",\"depth\":{}", entry.depth())?;
+    if let Some(target) = entry.link_target() {
+        match target.to_str() {
+            Some(target_str) => write!(stdout, ",\"target\":{}", escape(target_str))?,
+            None => write!(
+                stdout,
+                ",\"target_base64\":\"{}\"",
+                base64(&path_bytes(target))
+            )?,
+        }
+    }
+    write_xattrs(stdout, entry)?;
+    if let Some(kind) = config.checksum {
+        let checksum = entry.checksum().map(escape);
+        write!(stdout, ",\"{}\":{}", kind.name(), optional(checksum))?;
+    }
+    write!(stdout, "}}")
+}
+
+/// Write the extended attributes that matched the filters, if any, as `"xattrs"`. Values that
+/// are not valid UTF-8 are given as `"value_base64"`.
+#[cfg(unix)]
+fn write_xattrs<W: Write>(stdout: &mut W, entry: &DirEntry) -> io::Result<()> {
+    if entry.xattrs().is_empty() {
+        return Ok(());
+    }
+
+    write!(stdout, ",\"xattrs\":[")?;
+    for (i, (name, value)) in entry.xattrs().iter().enumerate() {
+        if i > 0 {
+            write!(stdout, ",")?;
+        }
+        write!(stdout, "{{\"name\":{},", escape(name))?;
+        let text = value.strip_suffix(b"\0").unwrap_or(value);
+        match std::str::from_utf8(text) {
+            Ok(text) => write!(stdout, "\"value\":{}}}", escape(text))?,
+            Err(_) => write!(stdout, "\"value_base64\":\"{}\"}}", base64(value))?,
+        }
+    }
+    write!(stdout, "]")
+}
+
+#[cfg(not(unix))]
+fn write_xattrs<W: Write>(_: &mut W, _: &DirEntry) -> io::Result<()> {
+    Ok(())
+}
+
+fn optional<T: ToString>(value: Option<T>) -> String {
+    value.map_or_else(|| "null".to_string(), |v| v.to_string())
+}
+
+/// The name of a file type, as used in the `"type"` field.
+pub fn file_type_name(file_type: FileType) -> &'static str {
+    #[cfg(unix)]
+    use std::os::unix::fs::FileTypeExt;
+
+    if file_type.is_file() {
+        return "file";
+    } else if file_type.is_dir() {
+        return "directory";
+    } else if file_type.is_symlink() {
+        return "symlink";
+    }
+
+    #[cfg(unix)]
+    {
+        if file_type.is_socket() {
+            return "socket";
+        } else if file_type.is_fifo() {
+            return "pipe";
+        } else if file_type.is_block_device() {
+            return "block-device";
+        } else if file_type.is_char_device() {
+            return "char-device";
+        }
+    }
+
+    "unknown"
+}
+
+#[cfg(unix)]
+fn mode(metadata: Option<&Metadata>) -> Option<String> {
+    use std::os::unix::fs::PermissionsExt;
+
+    metadata.map(|m| format!("\"{:04o}\"", m.permissions().mode() & 0o7777))
+}
+
+#[cfg(not(unix))]
+fn mode(_: Option<&Metadata>) -> Option<String> {
+    None
+}
+
+#[cfg(unix)]
+fn uid(metadata: Option<&Metadata>) -> Option<u32> {
+    metadata.map(fd_find::fshelper::get_uid)
+}
+
+#[cfg(not(unix))]
+fn uid(_: Option<&Metadata>) -> Option<u32> {
+    None
+}
+
+#[cfg(unix)]
+fn gid(metadata: Option<&Metadata>) -> Option<u32> {
+    metadata.map(fd_find::fshelper::get_gid)
+}
+
+#[cfg(not(unix))]
+fn gid(_: Option<&Metadata>) -> Option<u32> {
+    None
+}
+
+#[cfg(unix)]
+fn path_bytes(path: &Path) -> Vec<u8> {
+    use std::os::unix::ffi::OsStrExt;
+
+    path.as_os_str().as_bytes().to_vec()
+}
+
+#[cfg(not(unix))]
+fn path_bytes(path: &Path) -> Vec<u8> {
+    path.to_string_lossy().into_owned().into_bytes()
+}
+
+/// Quote a string as a JSON string literal.
+pub fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len() + 2);
+    escaped.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+/// Encode bytes with the standard base64 alphabet, with padding.
+pub fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut encoded = String::with_capacity(bytes.len() / 3 * 4 + 4);
+    for chunk in bytes.chunks(3) {
+        let b = [
+            chunk[0],
+            chunk.get(1).copied().unwrap_or(0),
+            chunk.get(2).copied().unwrap_or(0),
+        ];
+        let n = (u32::from(b[0]) << 16) | (u32::from(b[1]) << 8) | u32::from(b[2]);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escape_special_characters() {
+        assert_eq!(escape("foo"), r#""foo""#);
+        assert_eq!(escape(r#"a"b\c"#), r#""a\"b\\c""#);
+        assert_eq!(escape("a\nb\tc\u{1}"), r#""a\nb\tc\u0001""#);
+        assert_eq!(escape("ünïcödé"), "\"ünïcödé\"");
+    }
+
+    #[test]
+    fn base64_with_padding() {
+        assert_eq!(base64(b""), "");
+        assert_eq!(base64(b"f"), "Zg==");
+        assert_eq!(base64(b"fo"), "Zm8=");
+        assert_eq!(base64(b"foo"), "Zm9v");
+        assert_eq!(base64(b"foob"), "Zm9vYg==");
+        assert_eq!(base64(&[0xff, 0xfe, 0x80]), "//6A");
+    }
+}
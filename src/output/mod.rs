@@ -0,0 +1,300 @@
+// Copyright (c) 2017 fd developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0>
+// or the MIT license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+mod details;
+mod format;
+mod json;
+
+pub use self::format::FormatTemplate;
+
+use crate::exec::CommandTemplate;
+use crate::exit_codes::ExitCode;
+use crate::sort::SortKey;
+use fd_find::filter::ChecksumKind;
+use fd_find::DirEntry;
+use lscolors::{LsColors, Style};
+
+use std::borrow::Cow;
+use std::fs::Metadata;
+use std::io::{self, StdoutLock, Write};
+use std::path::{Component, Path};
+use std::process;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Options that control how the search results are presented.
+pub struct OutputOptions {
+    /// Whether elements of output should be separated by a null character
+    pub null_separator: bool,
+
+    /// Time to buffer results internally before streaming to the console. This is useful to
+    /// provide a sorted output, in case the total execution time is shorter than
+    /// `max_buffer_time`.
+    pub max_buffer_time: Option<Duration>,
+
+    /// The key to sort the results by. If set, all results are collected and sorted before they
+    /// are printed, instead of being streamed to the console.
+    pub sort: Option<SortKey>,
+
+    /// Whether to reverse the sort order.
+    pub reverse: bool,
+
+    /// The maximum number of sorted results to print. Unsorted results are limited by the search
+    /// itself.
+    pub max_results: Option<usize>,
+
+    /// `None` if the output should not be colorized. Otherwise, a `LsColors` instance that defines
+    /// how to style different filetypes.
+    pub ls_colors: Option<LsColors>,
+
+    /// If a value is supplied, each item found will be used to generate and execute commands.
+    pub command: Option<Arc<CommandTemplate>>,
+
+    /// Whether or not to display filesystem errors
+    pub show_filesystem_errors: bool,
+
+    /// The separator used to print file paths.
+    pub path_separator: Option<String>,
+
+    /// Whether to print each result as a line of JSON, including its metadata.
+    pub json: bool,
+
+    /// A user-defined format for each result.
+    pub format: Option<FormatTemplate>,
+
+    /// The checksum that is printed in front of each path (or added to the JSON output), if any.
+    pub checksum: Option<ChecksumKind>,
+
+    /// Whether the results are groups of files with identical contents. The maximum number of
+    /// results then limits the number of groups.
+    pub duplicates: bool,
+
+    /// Whether to print results in a long listing with permissions, owner, size and modification
+    /// time. This requires all results to be known, so they are always sorted.
+    pub list_details: bool,
+
+    /// Whether to print the target of symlinks after their path, like `path -> target`. This is
+    /// always done in a long listing.
+    pub show_link_target: bool,
+}
+
+/// Remove the `./` prefix from a path.
+fn strip_current_dir(pathbuf: &Path) -> &Path {
+    let mut iter = pathbuf.components();
+    let mut iter_next = iter.clone();
+    if iter_next.next() == Some(Component::CurDir) {
+        iter.next();
+    }
+    iter.as_path()
+}
+
+/// The path of an entry as it is printed.
+fn display_path(entry: &DirEntry) -> &Path {
+    let path = entry.path();
+    if path.is_absolute() {
+        path
+    } else {
+        strip_current_dir(path)
+    }
+}
+
+pub fn print_entry(
+    stdout: &mut StdoutLock,
+    entry: &DirEntry,
+    config: &OutputOptions,
+    wants_to_quit: &Arc<AtomicBool>,
+) {
+    let path = display_path(entry);
+
+    let r = if config.json {
+        json::print_entry(stdout, entry, path, config)
+    } else if let Some(ref format) = config.format {
+        format
+            .print_entry(stdout, entry, path, config)
+            .and_then(|_| print_separator(stdout, config))
+    } else {
+        print_checksum(stdout, entry, config)
+            .and_then(|_| match config.ls_colors {
+                Some(ref ls_colors) => {
+                    print_path_colorized(stdout, path, config, ls_colors, wants_to_quit)
+                }
+                None => print_path_uncolorized(stdout, path, config),
+            })
+            .and_then(|_| print_link_target(stdout, entry, config))
+            .and_then(|_| print_separator(stdout, config))
+    };
+
+    if r.is_err() {
+        // Probably a broken pipe. Exit gracefully.
+        process::exit(ExitCode::GeneralError.into());
+    }
+}
+
+/// Print all entries at once, in a long listing if requested.
+pub fn print_entries(
+    stdout: &mut StdoutLock,
+    entries: &[DirEntry],
+    config: &OutputOptions,
+    wants_to_quit: &Arc<AtomicBool>,
+) {
+    if config.list_details {
+        if details::print_entries(stdout, entries, config, wants_to_quit).is_err() {
+            // Probably a broken pipe. Exit gracefully.
+            process::exit(ExitCode::GeneralError.into());
+        }
+    } else {
+        for entry in entries {
+            print_entry(stdout, entry, config, wants_to_quit);
+        }
+    }
+}
+
+/// Split the entries into runs of the same duplicate group.
+fn duplicate_groups(entries: &[DirEntry]) -> Vec<&[DirEntry]> {
+    let mut groups = Vec::new();
+    let mut start = 0;
+    for i in 1..=entries.len() {
+        if i == entries.len() || entries[i].duplicate_group() != entries[start].duplicate_group() {
+            groups.push(&entries[start..i]);
+            start = i;
+        }
+    }
+    groups
+}
+
+/// Print groups of files with identical contents, separated by empty lines. Each group starts
+/// with a line that gives the size of the files and the number of bytes wasted on copies. With
+/// `--json`, each group is printed as a single line instead.
+pub fn print_duplicates(
+    stdout: &mut StdoutLock,
+    entries: &[DirEntry],
+    config: &OutputOptions,
+    wants_to_quit: &Arc<AtomicBool>,
+) {
+    let groups = duplicate_groups(entries)
+        .into_iter()
+        .take(config.max_results.unwrap_or(usize::MAX));
+
+    for (i, group) in groups.enumerate() {
+        let size = group[0].metadata().map_or(0, Metadata::len);
+        let wasted = size * (group.len() as u64 - 1);
+
+        let r = if config.json {
+            json::print_group(stdout, group, size, wasted, config)
+        } else {
+            let separator = if i > 0 { "\n" } else { "" };
+            writeln!(
+                stdout,
+                "{}{} identical files of {} bytes ({} bytes wasted):",
+                separator,
+                group.len(),
+                size,
+                wasted
+            )
+        };
+        if r.is_err() {
+            // Probably a broken pipe. Exit gracefully.
+            process::exit(ExitCode::GeneralError.into());
+        }
+
+        if !config.json {
+            for entry in group {
+                print_entry(stdout, entry, config, wants_to_quit);
+            }
+        }
+    }
+}
+
+fn replace_path_separator(config: &OutputOptions, path: &mut Cow<str>) {
+    match &config.path_separator {
+        None => {}
+        Some(sep) => {
+            *path.to_mut() = path.replace(std::path::MAIN_SEPARATOR, sep);
+        }
+    }
+}
+
+fn print_path_colorized(
+    stdout: &mut StdoutLock,
+    path: &Path,
+    config: &OutputOptions,
+    ls_colors: &LsColors,
+    wants_to_quit: &Arc<AtomicBool>,
+) -> io::Result<()> {
+    let default_style = ansi_term::Style::default();
+
+    // Traverse the path and colorize each component
+    for (component, style) in ls_colors.style_for_path_components(path) {
+        let style = style
+            .map(Style::to_ansi_term_style)
+            .unwrap_or(default_style);
+
+        let mut path_string = component.to_string_lossy();
+        replace_path_separator(config, &mut path_string);
+        write!(stdout, "{}", style.paint(path_string))?;
+
+        if wants_to_quit.load(Ordering::Relaxed) {
+            writeln!(stdout)?;
+            process::exit(ExitCode::KilledBySigint.into());
+        }
+    }
+
+    Ok(())
+}
+
+/// Print the checksum column in the format of `sha256sum`, i.e. followed by two spaces. Entries
+/// without a checksum get a `-` instead.
+fn print_checksum(
+    stdout: &mut StdoutLock,
+    entry: &DirEntry,
+    config: &OutputOptions,
+) -> io::Result<()> {
+    match config.checksum {
+        Some(kind) => write!(
+            stdout,
+            "{:<width$}  ",
+            entry.checksum().unwrap_or("-"),
+            width = kind.hex_len()
+        ),
+        None => Ok(()),
+    }
+}
+
+/// Print ` -> target` for symlinks, if link targets are shown.
+fn print_link_target(
+    stdout: &mut StdoutLock,
+    entry: &DirEntry,
+    config: &OutputOptions,
+) -> io::Result<()> {
+    if !config.show_link_target && !config.list_details {
+        return Ok(());
+    }
+    match entry.link_target() {
+        Some(target) => write!(stdout, " -> {}", target.to_string_lossy()),
+        None => Ok(()),
+    }
+}
+
+fn print_separator(stdout: &mut StdoutLock, config: &OutputOptions) -> io::Result<()> {
+    if config.null_separator {
+        write!(stdout, "\0")
+    } else {
+        writeln!(stdout)
+    }
+}
+
+fn print_path_uncolorized(
+    stdout: &mut StdoutLock,
+    path: &Path,
+    config: &OutputOptions,
+) -> io::Result<()> {
+    let mut path_str = path.to_string_lossy();
+    replace_path_separator(config, &mut path_str);
+    write!(stdout, "{}", path_str)
+}
@@ -0,0 +1,217 @@
+// Copyright (c) 2017 fd developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0>
+// or the MIT license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use crate::exec;
+use crate::exit_codes::{merge_exitcodes, ExitCode};
+use crate::output::{self, OutputOptions};
+use crate::sort;
+
+use std::io;
+use std::process;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time;
+
+use fd_find::{DirEntry, SearchResults};
+
+/// Maximum size of the output buffer before flushing results to the console
+pub const MAX_BUFFER_LENGTH: usize = 1000;
+
+/// The receiver thread can either be buffering results or directly streaming to the console.
+enum ReceiverMode {
+    /// Receiver is still buffering in order to sort the results, if the search finishes fast
+    /// enough.
+    Buffering,
+
+    /// Receiver is directly printing results to the output.
+    Streaming,
+}
+
+/// Consume the results of a running search.
+///
+/// If the `--exec` argument was supplied, this will create a thread pool for executing
+/// jobs in parallel from a given command line and the discovered paths. Otherwise, each
+/// path will simply be written to standard output.
+pub fn scan(results: SearchResults, config: Arc<OutputOptions>, threads: usize) -> ExitCode {
+    // The search also stops itself once the maximum number of results is reached, so Ctrl-C is
+    // tracked separately.
+    let interrupted = Arc::new(AtomicBool::new(false));
+    if config.ls_colors.is_some() && config.command.is_none() {
+        let wq = results.quit_flag();
+        let interrupted = Arc::clone(&interrupted);
+        ctrlc::set_handler(move || {
+            if interrupted.load(Ordering::Relaxed) {
+                // Ctrl-C has been pressed twice, exit NOW
+                process::exit(ExitCode::KilledBySigint.into());
+            } else {
+                interrupted.store(true, Ordering::Relaxed);
+                wq.store(true, Ordering::Relaxed);
+            }
+        })
+        .unwrap();
+    }
+
+    // Spawn the thread that receives all results.
+    let receiver_thread = spawn_receiver(&config, &interrupted, threads, results);
+
+    // Wait for the receiver thread to print out all results.
+    let exit_code = receiver_thread.join().unwrap();
+
+    if interrupted.load(Ordering::Relaxed) {
+        process::exit(ExitCode::KilledBySigint.into());
+    }
+
+    exit_code
+}
+
+fn spawn_receiver(
+    config: &Arc<OutputOptions>,
+    interrupted: &Arc<AtomicBool>,
+    threads: usize,
+    rx: SearchResults,
+) -> thread::JoinHandle<ExitCode> {
+    let config = Arc::clone(config);
+    let interrupted = Arc::clone(interrupted);
+
+    let show_filesystem_errors = config.show_filesystem_errors;
+
+    thread::spawn(move || {
+        // This will be set to `Some` if the `--exec` argument was supplied.
+        if let Some(ref cmd) = config.command {
+            if cmd.in_batch_mode() {
+                exec::batch(rx, cmd, show_filesystem_errors)
+            } else {
+                let shared_rx = Arc::new(Mutex::new(rx));
+
+                let out_perm = Arc::new(Mutex::new(()));
+
+                // Each spawned job will store it's thread handle in here.
+                let mut handles = Vec::with_capacity(threads);
+                for _ in 0..threads {
+                    let rx = Arc::clone(&shared_rx);
+                    let cmd = Arc::clone(cmd);
+                    let out_perm = Arc::clone(&out_perm);
+
+                    // Spawn a job thread that will listen for and execute inputs.
+                    let handle = thread::spawn(move || {
+                        exec::job(rx, cmd, out_perm, show_filesystem_errors)
+                    });
+
+                    // Push the handle of the spawned thread into the vector for later joining.
+                    handles.push(handle);
+                }
+
+                // Wait for all threads to exit before exiting the program.
+                let mut results: Vec<ExitCode> = Vec::new();
+                for h in handles {
+                    results.push(h.join().unwrap());
+                }
+
+                merge_exitcodes(results)
+            }
+        } else if config.duplicates {
+            let entries = collect_entries(rx, show_filesystem_errors);
+
+            let stdout = io::stdout();
+            let mut stdout = stdout.lock();
+            output::print_duplicates(&mut stdout, &entries, &config, &interrupted);
+
+            ExitCode::Success
+        } else if let Some(key) = config.sort {
+            let mut entries = collect_entries(rx, show_filesystem_errors);
+
+            sort::sort_entries(&mut entries, key, config.reverse);
+            if let Some(max_results) = config.max_results {
+                entries.truncate(max_results);
+            }
+
+            let stdout = io::stdout();
+            let mut stdout = stdout.lock();
+            output::print_entries(&mut stdout, &entries, &config, &interrupted);
+
+            ExitCode::Success
+        } else {
+            let start = time::Instant::now();
+
+            let mut buffer = vec![];
+
+            // Start in buffering mode
+            let mut mode = ReceiverMode::Buffering;
+
+            // Maximum time to wait before we start streaming to the console.
+            let max_buffer_time = config
+                .max_buffer_time
+                .unwrap_or_else(|| time::Duration::from_millis(100));
+
+            let stdout = io::stdout();
+            let mut stdout = stdout.lock();
+
+            for result in rx {
+                match result {
+                    Ok(entry) => {
+                        match mode {
+                            ReceiverMode::Buffering => {
+                                buffer.push(entry);
+
+                                // Have we reached the maximum buffer size or maximum buffering time?
+                                if buffer.len() > MAX_BUFFER_LENGTH
+                                    || time::Instant::now() - start > max_buffer_time
+                                {
+                                    // Flush the buffer
+                                    for v in &buffer {
+                                        output::print_entry(&mut stdout, v, &config, &interrupted);
+                                    }
+                                    buffer.clear();
+
+                                    // Start streaming
+                                    mode = ReceiverMode::Streaming;
+                                }
+                            }
+                            ReceiverMode::Streaming => {
+                                output::print_entry(&mut stdout, &entry, &config, &interrupted);
+                            }
+                        }
+                    }
+                    Err(err) => {
+                        if show_filesystem_errors {
+                            print_error!("{}", err);
+                        }
+                    }
+                }
+            }
+
+            // If we have finished fast enough (faster than max_buffer_time), we haven't streamed
+            // anything to the console, yet. In this case, sort the results and print them:
+            if !buffer.is_empty() {
+                buffer.sort_by(|a, b| a.path().cmp(b.path()));
+                for entry in buffer {
+                    output::print_entry(&mut stdout, &entry, &config, &interrupted);
+                }
+            }
+
+            ExitCode::Success
+        }
+    })
+}
+
+/// Wait for all results of the search, and report the errors right away.
+fn collect_entries(rx: SearchResults, show_filesystem_errors: bool) -> Vec<DirEntry> {
+    let mut entries = vec![];
+    for result in rx {
+        match result {
+            Ok(entry) => entries.push(entry),
+            Err(err) => {
+                if show_filesystem_errors {
+                    print_error!("{}", err);
+                }
+            }
+        }
+    }
+    entries
+}
@@ -0,0 +1,543 @@
+// Copyright (c) 2017 fd developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0>
+// or the MIT license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::Receiver;
+use std::sync::Arc;
+
+use regex::bytes::{RegexBuilder, RegexSetBuilder};
+
+use crate::error::Error;
+use crate::internal::filter::*;
+use crate::internal::{opts::FdOptions, pattern_has_uppercase_char, FileTypes};
+use crate::walk::{self, DirEntry, WorkerResult};
+
+/// A builder for a file system search.
+///
+/// The search pattern is a regular expression that is matched against the file name (or the full
+/// path, see `full_path`). Like the *fd* binary, the search is case-insensitive unless the pattern
+/// contains an uppercase character.
+pub struct Searcher {
+    pattern: String,
+    roots: Vec<PathBuf>,
+    extensions: Vec<String>,
+    options: FdOptions,
+}
+
+impl Searcher {
+    /// Create a new search for the given regular expression, using the default options.
+    pub fn new<S: Into<String>>(pattern: S) -> Searcher {
+        let pattern = pattern.into();
+        let options = FdOptions {
+            case_sensitive: pattern_has_uppercase_char(&pattern),
+            ..FdOptions::default()
+        };
+
+        Searcher {
+            pattern,
+            roots: vec![],
+            extensions: vec![],
+            options,
+        }
+    }
+
+    /// Replace all options at once. This overrides every option that has been set before.
+    pub fn options(mut self, options: FdOptions) -> Searcher {
+        self.options = options;
+        self
+    }
+
+    /// Add a directory to search in. If no root is given, the current directory is searched.
+    pub fn root<P: Into<PathBuf>>(mut self, path: P) -> Searcher {
+        self.roots.push(path.into());
+        self
+    }
+
+    /// Add several directories to search in.
+    pub fn roots<I, P>(mut self, paths: I) -> Searcher
+    where
+        I: IntoIterator<Item = P>,
+        P: Into<PathBuf>,
+    {
+        self.roots.extend(paths.into_iter().map(Into::into));
+        self
+    }
+
+    /// Whether the pattern is matched case-sensitively.
+    pub fn case_sensitive(mut self, yes: bool) -> Searcher {
+        self.options.case_sensitive = yes;
+        self
+    }
+
+    /// Whether the pattern is matched against the full path instead of the file name.
+    pub fn full_path(mut self, yes: bool) -> Searcher {
+        self.options.search_full_path = yes;
+        self
+    }
+
+    /// Whether hidden files and directories are included in the results.
+    pub fn hidden(mut self, yes: bool) -> Searcher {
+        self.options.ignore_hidden = !yes;
+        self
+    }
+
+    /// Whether `.ignore` and `.fdignore` files are respected.
+    pub fn read_ignore(mut self, yes: bool) -> Searcher {
+        self.options.read_fdignore = yes;
+        self
+    }
+
+    /// Whether VCS ignore files (`.gitignore`, ..) are respected.
+    pub fn read_vcsignore(mut self, yes: bool) -> Searcher {
+        self.options.read_vcsignore = yes;
+        self
+    }
+
+    /// Add a custom ignore file in `.gitignore` format.
+    pub fn ignore_file<P: Into<PathBuf>>(mut self, path: P) -> Searcher {
+        self.options.ignore_files.push(path.into());
+        self
+    }
+
+    /// Exclude entries that match the given glob pattern.
+    pub fn exclude(mut self, glob: &str) -> Searcher {
+        self.options.exclude_patterns.push(String::from("!") + glob);
+        self
+    }
+
+    /// Whether symbolic links are followed.
+    pub fn follow_links(mut self, yes: bool) -> Searcher {
+        self.options.follow_links = yes;
+        self
+    }
+
+    /// Whether the search is limited to the file system it started in.
+    pub fn one_file_system(mut self, yes: bool) -> Searcher {
+        self.options.one_file_system = yes;
+        self
+    }
+
+    /// Limit the traversal to the given depth.
+    pub fn max_depth(mut self, depth: Option<usize>) -> Searcher {
+        self.options.max_depth = depth;
+        self
+    }
+
+    /// Only report entries at or below the given depth. The traversal still starts at the roots.
+    pub fn min_depth(mut self, depth: Option<usize>) -> Searcher {
+        self.options.min_depth = depth;
+        self
+    }
+
+    /// Only report entries at exactly the given depth.
+    pub fn exact_depth(self, depth: usize) -> Searcher {
+        self.min_depth(Some(depth)).max_depth(Some(depth))
+    }
+
+    /// Whether directories that match the search are reported without descending into them.
+    pub fn prune(mut self, yes: bool) -> Searcher {
+        self.options.prune = yes;
+        self
+    }
+
+    /// Set the number of threads used for the traversal.
+    pub fn threads(mut self, threads: usize) -> Searcher {
+        self.options.threads = std::cmp::max(threads, 1);
+        self
+    }
+
+    /// Only include entries of the given types.
+    pub fn file_types(mut self, file_types: FileTypes) -> Searcher {
+        self.options.file_types = Some(file_types);
+        self
+    }
+
+    /// Only include entries with the given file extension. Can be called repeatedly to allow for
+    /// several extensions.
+    pub fn extension(mut self, extension: &str) -> Searcher {
+        self.extensions.push(extension.to_owned());
+        self
+    }
+
+    /// Only include entries with one of the given file extensions.
+    pub fn extensions<I, S>(mut self, extensions: I) -> Searcher
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.extensions
+            .extend(extensions.into_iter().map(|e| e.as_ref().to_owned()));
+        self
+    }
+
+    /// Add a constraint on the size of files.
+    pub fn size(mut self, filter: SizeFilter) -> Searcher {
+        self.options.size_constraints.push(filter);
+        self
+    }
+
+    /// Compute the total size of directories (including everything below them), so that size
+    /// constraints apply to directories as well. The totals are reported by `DirEntry::size`.
+    pub fn directory_sizes(mut self, yes: bool) -> Searcher {
+        self.options.directory_sizes = yes;
+        self
+    }
+
+    /// Compare (and report) the apparent or the allocated size of entries.
+    pub fn size_kind(mut self, kind: SizeKind) -> Searcher {
+        self.options.size_kind = kind;
+        self
+    }
+
+    /// Add a constraint on the modification time of entries.
+    pub fn time(mut self, filter: TimeFilter) -> Searcher {
+        self.options.time_constraints.push(filter);
+        self
+    }
+
+    /// Add a filter on the owning user and/or group.
+    #[cfg(unix)]
+    pub fn owner(mut self, filter: OwnerFilter) -> Searcher {
+        self.options.owner_filters.push(filter);
+        self
+    }
+
+    /// Only include files whose owner has no entry in the user database, e.g. because the account
+    /// was deleted.
+    #[cfg(unix)]
+    pub fn no_user(mut self, yes: bool) -> Searcher {
+        self.options.no_user = yes;
+        self
+    }
+
+    /// Only include files whose group has no entry in the group database.
+    #[cfg(unix)]
+    pub fn no_group(mut self, yes: bool) -> Searcher {
+        self.options.no_group = yes;
+        self
+    }
+
+    /// Add a filter on the permission bits.
+    #[cfg(unix)]
+    pub fn permission(mut self, filter: PermissionFilter) -> Searcher {
+        self.options.permission_filters.push(filter);
+        self
+    }
+
+    /// Add a filter on the target of symlinks. Only symlinks whose target matches all of these
+    /// filters are included.
+    pub fn link_target(mut self, filter: LinkTargetFilter) -> Searcher {
+        self.options.link_target_filters.push(filter);
+        self
+    }
+
+    /// Add a pattern for the MIME type of files, which is detected from their first bytes. Only
+    /// regular files whose type matches one of the patterns are included.
+    pub fn mime(mut self, filter: MimeFilter) -> Searcher {
+        self.options.mime_filters.push(filter);
+        self
+    }
+
+    /// Add a filter on the contents of files.
+    pub fn content(mut self, filter: ContentFilter) -> Searcher {
+        self.options.content_filters.push(filter);
+        self
+    }
+
+    /// Compute the checksum of every regular file, in the walker threads. It is reported by
+    /// `DirEntry::checksum`.
+    pub fn checksum(mut self, kind: ChecksumKind) -> Searcher {
+        self.options.checksum = Some(kind);
+        self
+    }
+
+    /// Only include regular files with the given checksum.
+    pub fn checksum_matches(mut self, filter: ChecksumFilter) -> Searcher {
+        self.options.checksum_filters.push(filter);
+        self
+    }
+
+    /// Add a filter on the number of hard links.
+    #[cfg(unix)]
+    pub fn links(mut self, filter: LinksFilter) -> Searcher {
+        self.options.links_filters.push(filter);
+        self
+    }
+
+    /// Add a filter on the inode number, or on being the same file as another one.
+    #[cfg(unix)]
+    pub fn inode(mut self, filter: InodeFilter) -> Searcher {
+        self.options.inode_filters.push(filter);
+        self
+    }
+
+    /// Report each inode only once, even if several hard links to it are found.
+    #[cfg(unix)]
+    pub fn unique_inode(mut self, yes: bool) -> Searcher {
+        self.options.unique_inode = yes;
+        self
+    }
+
+    /// Only report regular files whose contents are identical to those of another result. The
+    /// files are reported group by group once the traversal has finished, and
+    /// `DirEntry::duplicate_group` tells the groups apart. Empty files are not reported.
+    pub fn duplicates(mut self, yes: bool) -> Searcher {
+        self.options.duplicates = yes;
+        self
+    }
+
+    /// Add a filter on whether the current (or a given) user may read, write or execute the
+    /// entries.
+    #[cfg(unix)]
+    pub fn access(mut self, filter: AccessFilter) -> Searcher {
+        self.options.access_filters.push(filter);
+        self
+    }
+
+    /// Add a filter on extended attributes. The attributes that matched are reported by
+    /// `DirEntry::xattrs`.
+    #[cfg(unix)]
+    pub fn xattr(mut self, filter: XattrFilter) -> Searcher {
+        self.options.xattr_filters.push(filter);
+        self
+    }
+
+    /// Stop the search after the given number of entries has been found.
+    pub fn max_results(mut self, count: Option<usize>) -> Searcher {
+        self.options.max_results = count;
+        self
+    }
+
+    /// Only include entries for which the expression holds. If called repeatedly, all of the
+    /// expressions have to hold.
+    pub fn filter_expression(mut self, expr: FilterExpr) -> Searcher {
+        self.options.filter_expression = Some(match self.options.filter_expression.take() {
+            Some(previous) => FilterExpr::And(Box::new(previous), Box::new(expr)),
+            None => expr,
+        });
+        self
+    }
+
+    /// Start the search.
+    ///
+    /// The traversal runs on background threads. Its results can be consumed through the
+    /// returned iterator while the search is still in progress.
+    pub fn search(mut self) -> Result<SearchResults, Error> {
+        if !self.extensions.is_empty() {
+            let patterns = self
+                .extensions
+                .iter()
+                .map(|e| e.trim_start_matches('.'))
+                .map(|e| format!(r".\.{}$", regex::escape(e)));
+            let exts_regex = RegexSetBuilder::new(patterns)
+                .case_insensitive(true)
+                .build()
+                .map_err(Error::InvalidExtension)?;
+            self.options.extensions = Some(exts_regex);
+        }
+
+        let pattern = RegexBuilder::new(&self.pattern)
+            .case_insensitive(!self.options.case_sensitive)
+            .dot_matches_new_line(true)
+            .build()
+            .map_err(Error::InvalidPattern)?;
+
+        if self.roots.is_empty() {
+            self.roots.push(PathBuf::from("."));
+        }
+
+        walk::scan(&self.roots, Arc::new(pattern), Arc::new(self.options))
+    }
+}
+
+/// The results of a running search.
+///
+/// Each item is either an entry that matched the search or an error that was encountered during
+/// the traversal. The iterator ends once the traversal has finished, or once the maximum number of
+/// results has been yielded. Dropping it stops the traversal.
+pub struct SearchResults {
+    rx: Receiver<WorkerResult>,
+    wants_to_quit: Arc<AtomicBool>,
+    remaining: Option<usize>,
+}
+
+impl SearchResults {
+    pub(crate) fn new(
+        rx: Receiver<WorkerResult>,
+        wants_to_quit: Arc<AtomicBool>,
+        max_results: Option<usize>,
+    ) -> SearchResults {
+        SearchResults {
+            rx,
+            wants_to_quit,
+            remaining: max_results,
+        }
+    }
+
+    /// A flag that stops the traversal once it is set, for example from a signal handler.
+    pub fn quit_flag(&self) -> Arc<AtomicBool> {
+        Arc::clone(&self.wants_to_quit)
+    }
+}
+
+impl Iterator for SearchResults {
+    type Item = Result<DirEntry, ignore::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == Some(0) {
+            self.wants_to_quit.store(true, Ordering::Relaxed);
+            return None;
+        }
+
+        self.rx.recv().ok().map(|result| match result {
+            WorkerResult::Entry(entry) => {
+                if let Some(ref mut remaining) = self.remaining {
+                    *remaining -= 1;
+                    if *remaining == 0 {
+                        // Stop the walker threads right away, not only on the next call.
+                        self.wants_to_quit.store(true, Ordering::Relaxed);
+                    }
+                }
+                Ok(*entry)
+            }
+            WorkerResult::Error(err) => Err(err),
+        })
+    }
+}
+
+impl Drop for SearchResults {
+    fn drop(&mut self) {
+        self.wants_to_quit.store(true, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::path::Path;
+    use tempdir::TempDir;
+
+    fn create_tree(files: &[&str]) -> TempDir {
+        let temp_dir = TempDir::new("fd-searcher").unwrap();
+        for file in files {
+            let path = temp_dir.path().join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::File::create(path).unwrap();
+        }
+        temp_dir
+    }
+
+    fn search_paths(searcher: Searcher, root: &Path) -> Vec<PathBuf> {
+        let mut paths: Vec<_> = searcher
+            .search()
+            .unwrap()
+            .map(|result| {
+                let entry = result.unwrap();
+                entry.path().strip_prefix(root).unwrap().to_path_buf()
+            })
+            .collect();
+        paths.sort();
+        paths
+    }
+
+    #[test]
+    fn search_with_pattern_and_extension() {
+        let tree = create_tree(&["a.foo", "b.bar", "one/c.foo", "one/Foo.txt"]);
+        let root = tree.path();
+
+        let searcher = Searcher::new("").root(root).extension("foo");
+        assert_eq!(
+            search_paths(searcher, root),
+            vec![PathBuf::from("a.foo"), PathBuf::from("one/c.foo")]
+        );
+
+        // Smart case: an uppercase character makes the search case-sensitive.
+        let searcher = Searcher::new("Foo").root(root);
+        assert_eq!(
+            search_paths(searcher, root),
+            vec![PathBuf::from("one/Foo.txt")]
+        );
+    }
+
+    #[test]
+    fn max_results_stops_the_search() {
+        let tree = create_tree(&["a.foo", "b.foo", "c.foo", "one/d.foo"]);
+        let root = tree.path();
+
+        let searcher = Searcher::new("foo").root(root).max_results(Some(2));
+        assert_eq!(search_paths(searcher, root).len(), 2);
+
+        let mut results = Searcher::new("foo")
+            .root(root)
+            .max_results(Some(1))
+            .search()
+            .unwrap();
+        assert!(results.next().is_some());
+        assert!(results.quit_flag().load(Ordering::Relaxed));
+        assert!(results.next().is_none());
+
+        let results = Searcher::new("foo").root(root).search().unwrap();
+        let quit = results.quit_flag();
+        assert!(!quit.load(Ordering::Relaxed));
+        drop(results);
+        assert!(quit.load(Ordering::Relaxed));
+    }
+
+    #[test]
+    fn group_duplicates() {
+        let tree = create_tree(&["empty1", "empty2"]);
+        let root = tree.path();
+        let large = vec![b'x'; 10_000];
+        let mut large_variant = large.clone();
+        *large_variant.last_mut().unwrap() = b'y';
+        for (name, contents) in &[
+            ("a", &b"small"[..]),
+            ("sub/b", b"small"),
+            ("c", b"other"),
+            ("d", &large),
+            ("e", &large_variant),
+            ("sub/f", &large),
+            ("sub/g", &large),
+        ] {
+            fs::create_dir_all(root.join(name).parent().unwrap()).unwrap();
+            fs::write(root.join(name), contents).unwrap();
+        }
+
+        let groups: Vec<_> = Searcher::new("")
+            .root(root)
+            .duplicates(true)
+            .search()
+            .unwrap()
+            .map(|result| {
+                let entry = result.unwrap();
+                let path = entry.path().strip_prefix(root).unwrap().to_path_buf();
+                (entry.duplicate_group().unwrap(), path)
+            })
+            .collect();
+        assert_eq!(
+            groups,
+            vec![
+                (0, PathBuf::from("a")),
+                (0, PathBuf::from("sub/b")),
+                (1, PathBuf::from("d")),
+                (1, PathBuf::from("sub/f")),
+                (1, PathBuf::from("sub/g")),
+            ]
+        );
+    }
+
+    #[test]
+    fn invalid_pattern_is_an_error() {
+        match Searcher::new("(").search() {
+            Err(Error::InvalidPattern(_)) => {}
+            _ => panic!("expected an invalid pattern error"),
+        }
+    }
+}
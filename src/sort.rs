@@ -0,0 +1,79 @@
+// Copyright (c) 2017 fd developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0>
+// or the MIT license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use std::cmp::Reverse;
+use std::ffi::OsString;
+
+use fd_find::DirEntry;
+
+/// The key by which search results are sorted.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SortKey {
+    Path,
+    Name,
+    Size,
+    ModificationTime,
+    AccessTime,
+    Depth,
+    Extension,
+}
+
+impl SortKey {
+    pub fn from_string(s: &str) -> Option<SortKey> {
+        match s {
+            "path" => Some(SortKey::Path),
+            "name" => Some(SortKey::Name),
+            "size" => Some(SortKey::Size),
+            "mtime" => Some(SortKey::ModificationTime),
+            "atime" => Some(SortKey::AccessTime),
+            "depth" => Some(SortKey::Depth),
+            "extension" => Some(SortKey::Extension),
+            _ => None,
+        }
+    }
+}
+
+/// Sort the entries by the given key. Entries with equal keys are ordered by their path, also if
+/// the order is reversed.
+///
+/// The metadata of an entry is only read if the key requires it, and then only once per entry.
+pub fn sort_entries(entries: &mut [DirEntry], key: SortKey, reverse: bool) {
+    entries.sort_by(|a, b| a.path().cmp(b.path()));
+
+    match key {
+        SortKey::Path => {
+            if reverse {
+                entries.reverse();
+            }
+        }
+        SortKey::Name => sort_by_key(entries, reverse, |e| {
+            e.path().file_name().map(OsString::from)
+        }),
+        SortKey::Size => sort_by_key(entries, reverse, DirEntry::size),
+        SortKey::ModificationTime => sort_by_key(entries, reverse, |e| {
+            e.metadata().and_then(|m| m.modified().ok())
+        }),
+        SortKey::AccessTime => sort_by_key(entries, reverse, |e| {
+            e.metadata().and_then(|m| m.accessed().ok())
+        }),
+        SortKey::Depth => sort_by_key(entries, reverse, DirEntry::depth),
+        SortKey::Extension => sort_by_key(entries, reverse, |e| {
+            e.path().extension().map(OsString::from)
+        }),
+    }
+}
+
+/// Sort entries that are already ordered by path by a key, which is computed once per entry. The
+/// sort is stable, so entries with equal keys stay in path order.
+fn sort_by_key<K: Ord>(entries: &mut [DirEntry], reverse: bool, key: impl Fn(&DirEntry) -> K) {
+    if reverse {
+        entries.sort_by_cached_key(|e| Reverse(key(e)));
+    } else {
+        entries.sort_by_cached_key(key);
+    }
+}
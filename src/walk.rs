@@ -6,51 +6,49 @@
 // notice may not be copied, modified, or distributed except
 // according to those terms.
 
-use crate::exec;
-use crate::exit_codes::{merge_exitcodes, ExitCode};
+use crate::duplicates::Duplicates;
+use crate::error::Error;
 use crate::fshelper;
-use crate::internal::{opts::FdOptions, osstr_to_bytes, MAX_BUFFER_LENGTH};
-use crate::output;
+#[cfg(unix)]
+use crate::internal::filter::Xattr;
+use crate::internal::filter::{self, SizeKind};
+use crate::internal::{opts::FdOptions, osstr_to_bytes, DirSizes};
+use crate::search::SearchResults;
 
 use std::borrow::Cow;
-use std::error::Error;
+use std::cell::OnceCell;
+#[cfg(unix)]
+use std::collections::HashSet;
 use std::ffi::OsStr;
-use std::fs::{FileType, Metadata};
+use std::fs::{self, FileType, Metadata};
 use std::io;
 use std::path::{Path, PathBuf};
-use std::process;
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::mpsc::{channel, Receiver, Sender};
-use std::sync::{Arc, Mutex};
+use std::sync::mpsc::{channel, Sender};
+use std::sync::Arc;
+#[cfg(unix)]
+use std::sync::Mutex;
 use std::thread;
-use std::time;
 
 use ignore::overrides::OverrideBuilder;
 use ignore::{self, WalkBuilder};
 use regex::bytes::Regex;
 
-/// The receiver thread can either be buffering results or directly streaming to the console.
-enum ReceiverMode {
-    /// Receiver is still buffering in order to sort the results, if the search finishes fast
-    /// enough.
-    Buffering,
-
-    /// Receiver is directly printing results to the output.
-    Streaming,
-}
-
-/// The Worker threads can result in a valid entry having PathBuf or an error.
+/// The Worker threads can result in a valid entry or an error.
 pub enum WorkerResult {
-    Entry(PathBuf),
+    Entry(Box<DirEntry>),
     Error(ignore::Error),
 }
 
 /// Recursively scan the given search path for files / pathnames matching the pattern.
 ///
-/// If the `--exec` argument was supplied, this will create a thread pool for executing
-/// jobs in parallel from a given command line and the discovered paths. Otherwise, each
-/// path will simply be written to standard output.
-pub fn scan(path_vec: &[PathBuf], pattern: Arc<Regex>, config: Arc<FdOptions>) -> ExitCode {
+/// The traversal runs on a pool of worker threads in the background. Each matching entry is
+/// sent through the returned `SearchResults` as soon as it is found.
+pub fn scan(
+    path_vec: &[PathBuf],
+    pattern: Arc<Regex>,
+    config: Arc<FdOptions>,
+) -> Result<SearchResults, Error> {
     let mut path_iter = path_vec.iter();
     let first_path_buf = path_iter
         .next()
@@ -60,14 +58,11 @@ pub fn scan(path_vec: &[PathBuf], pattern: Arc<Regex>, config: Arc<FdOptions>) -
     let mut override_builder = OverrideBuilder::new(first_path_buf.as_path());
 
     for pattern in &config.exclude_patterns {
-        let res = override_builder.add(pattern);
-        if res.is_err() {
-            print_error_and_exit!("Malformed exclude pattern '{}'", pattern);
+        if override_builder.add(pattern).is_err() {
+            return Err(Error::InvalidExcludePattern(pattern.clone()));
         }
     }
-    let overrides = override_builder.build().unwrap_or_else(|_| {
-        print_error_and_exit!("Mismatch in exclude patterns");
-    });
+    let overrides = override_builder.build().map_err(Error::ExcludePatterns)?;
 
     let mut walker = WalkBuilder::new(first_path_buf.as_path());
     walker
@@ -92,14 +87,7 @@ pub fn scan(path_vec: &[PathBuf], pattern: Arc<Regex>, config: Arc<FdOptions>) -
         match result {
             Some(ignore::Error::Partial(_)) => (),
             Some(err) => {
-                print_error!(
-                    "{}",
-                    format!(
-                        "Malformed pattern in custom ignore file '{}': {}.",
-                        ignore_file.to_string_lossy(),
-                        err.description()
-                    )
-                );
+                return Err(Error::InvalidIgnoreFile(ignore_file.clone(), err));
             }
             None => (),
         }
@@ -112,174 +100,221 @@ pub fn scan(path_vec: &[PathBuf], pattern: Arc<Regex>, config: Arc<FdOptions>) -
     let parallel_walker = walker.threads(config.threads).build_parallel();
 
     let wants_to_quit = Arc::new(AtomicBool::new(false));
-    if config.ls_colors.is_some() && config.command.is_none() {
-        let wq = Arc::clone(&wants_to_quit);
-        ctrlc::set_handler(move || {
-            if wq.load(Ordering::Relaxed) {
-                // Ctrl-C has been pressed twice, exit NOW
-                process::exit(ExitCode::KilledBySigint.into());
+    let max_results = config.max_results;
+
+    // Spawn the sender threads.
+    {
+        let wants_to_quit = Arc::clone(&wants_to_quit);
+        let shared = Arc::new(SharedState {
+            roots: path_vec.to_vec(),
+            dir_sizes: if config.directory_sizes {
+                Some(DirSizes::new(config.size_kind, config.one_file_system))
             } else {
-                wq.store(true, Ordering::Relaxed);
-            }
-        })
-        .unwrap();
+                None
+            },
+            duplicates: if config.duplicates {
+                Some(Duplicates::default())
+            } else {
+                None
+            },
+            #[cfg(unix)]
+            seen_inodes: if config.unique_inode {
+                Some(Mutex::new(HashSet::new()))
+            } else {
+                None
+            },
+        });
+        thread::spawn(move || {
+            spawn_senders(
+                &config,
+                &wants_to_quit,
+                pattern,
+                &shared,
+                parallel_walker,
+                &tx,
+            );
+            send_duplicates(&shared, &tx);
+        });
     }
 
-    // Spawn the thread that receives all results through the channel.
-    let receiver_thread = spawn_receiver(&config, &wants_to_quit, rx);
-
-    // Spawn the sender threads.
-    spawn_senders(&config, &wants_to_quit, pattern, parallel_walker, tx);
+    Ok(SearchResults::new(rx, wants_to_quit, max_results))
+}
 
-    // Wait for the receiver thread to print out all results.
-    let exit_code = receiver_thread.join().unwrap();
+/// The state that is shared by all walker threads of a search.
+struct SharedState {
+    /// The search roots.
+    roots: Vec<PathBuf>,
+    /// The total sizes of directories, if they are computed.
+    dir_sizes: Option<DirSizes>,
+    /// The files that are compared by their contents, if only duplicates are reported.
+    duplicates: Option<Duplicates>,
+    /// The device and inode numbers of the entries that were sent so far, if every inode is to
+    /// be reported only once.
+    #[cfg(unix)]
+    seen_inodes: Option<Mutex<HashSet<(u64, u64)>>>,
+}
 
-    if wants_to_quit.load(Ordering::Relaxed) {
-        process::exit(ExitCode::KilledBySigint.into());
-    }
+enum DirEntryInner {
+    Normal(ignore::DirEntry),
+    BrokenSymlink { path: PathBuf, depth: usize },
+}
 
-    exit_code
+/// A file system entry that matched the search.
+pub struct DirEntry {
+    inner: DirEntryInner,
+    /// The metadata is read at most once, when it is first needed (usually by one of the filters).
+    metadata: OnceCell<Option<Metadata>>,
+    /// The size computed by the walker, if it differs from the apparent size in the metadata.
+    size: OnceCell<u64>,
+    /// The MIME type of a regular file, detected when it is first needed.
+    mime_type: OnceCell<Option<&'static str>>,
+    /// The extended attributes that matched the filters.
+    #[cfg(unix)]
+    xattrs: OnceCell<Vec<Xattr>>,
+    /// The target of a symlink, read when it is first needed.
+    link_target: OnceCell<Option<PathBuf>>,
+    /// The checksum of a regular file, if it is computed.
+    checksum: OnceCell<String>,
+    /// The group of files with identical contents, if duplicates are searched.
+    duplicate_group: Option<usize>,
 }
 
-fn spawn_receiver(
-    config: &Arc<FdOptions>,
-    wants_to_quit: &Arc<AtomicBool>,
-    rx: Receiver<WorkerResult>,
-) -> thread::JoinHandle<ExitCode> {
-    let config = Arc::clone(config);
-    let wants_to_quit = Arc::clone(wants_to_quit);
-
-    let show_filesystem_errors = config.show_filesystem_errors;
-    let threads = config.threads;
-
-    thread::spawn(move || {
-        // This will be set to `Some` if the `--exec` argument was supplied.
-        if let Some(ref cmd) = config.command {
-            if cmd.in_batch_mode() {
-                exec::batch(rx, cmd, show_filesystem_errors)
-            } else {
-                let shared_rx = Arc::new(Mutex::new(rx));
+impl DirEntry {
+    fn normal(e: ignore::DirEntry) -> DirEntry {
+        DirEntry {
+            inner: DirEntryInner::Normal(e),
+            metadata: OnceCell::new(),
+            size: OnceCell::new(),
+            mime_type: OnceCell::new(),
+            #[cfg(unix)]
+            xattrs: OnceCell::new(),
+            link_target: OnceCell::new(),
+            checksum: OnceCell::new(),
+            duplicate_group: None,
+        }
+    }
 
-                let out_perm = Arc::new(Mutex::new(()));
+    fn broken_symlink(path: PathBuf, depth: usize) -> DirEntry {
+        DirEntry {
+            inner: DirEntryInner::BrokenSymlink { path, depth },
+            metadata: OnceCell::new(),
+            size: OnceCell::new(),
+            mime_type: OnceCell::new(),
+            #[cfg(unix)]
+            xattrs: OnceCell::new(),
+            link_target: OnceCell::new(),
+            checksum: OnceCell::new(),
+            duplicate_group: None,
+        }
+    }
 
-                // Each spawned job will store it's thread handle in here.
-                let mut handles = Vec::with_capacity(threads);
-                for _ in 0..threads {
-                    let rx = Arc::clone(&shared_rx);
-                    let cmd = Arc::clone(cmd);
-                    let out_perm = Arc::clone(&out_perm);
+    /// The path of the entry, starting with the search root it was found under.
+    pub fn path(&self) -> &Path {
+        match &self.inner {
+            DirEntryInner::Normal(e) => e.path(),
+            DirEntryInner::BrokenSymlink { path, .. } => path.as_path(),
+        }
+    }
 
-                    // Spawn a job thread that will listen for and execute inputs.
-                    let handle =
-                        thread::spawn(move || exec::job(rx, cmd, out_perm, show_filesystem_errors));
+    /// The file type of the entry, or `None` if it could not be determined.
+    pub fn file_type(&self) -> Option<FileType> {
+        match &self.inner {
+            DirEntryInner::Normal(e) => e.file_type(),
+            DirEntryInner::BrokenSymlink { .. } => self.metadata().map(|m| m.file_type()),
+        }
+    }
 
-                    // Push the handle of the spawned thread into the vector for later joining.
-                    handles.push(handle);
-                }
+    /// The metadata of the entry, or `None` if it could not be read. For a broken symlink, this
+    /// is the metadata of the link itself.
+    pub fn metadata(&self) -> Option<&Metadata> {
+        self.metadata
+            .get_or_init(|| match &self.inner {
+                DirEntryInner::Normal(e) => e.metadata().ok(),
+                DirEntryInner::BrokenSymlink { path, .. } => path.symlink_metadata().ok(),
+            })
+            .as_ref()
+    }
 
-                // Wait for all threads to exit before exiting the program.
-                let mut results: Vec<ExitCode> = Vec::new();
-                for h in handles {
-                    results.push(h.join().unwrap());
-                }
+    /// The size of the entry. This is the total size of a directory if directory sizes are
+    /// computed, and the allocated size if that is compared. Otherwise, it is the apparent size
+    /// from the metadata.
+    pub fn size(&self) -> Option<u64> {
+        self.size
+            .get()
+            .copied()
+            .or_else(|| self.metadata().map(Metadata::len))
+    }
 
-                merge_exitcodes(results)
-            }
-        } else {
-            let start = time::Instant::now();
-
-            let mut buffer = vec![];
-
-            // Start in buffering mode
-            let mut mode = ReceiverMode::Buffering;
-
-            // Maximum time to wait before we start streaming to the console.
-            let max_buffer_time = config
-                .max_buffer_time
-                .unwrap_or_else(|| time::Duration::from_millis(100));
-
-            let stdout = io::stdout();
-            let mut stdout = stdout.lock();
-
-            for worker_result in rx {
-                match worker_result {
-                    WorkerResult::Entry(value) => {
-                        match mode {
-                            ReceiverMode::Buffering => {
-                                buffer.push(value);
-
-                                // Have we reached the maximum buffer size or maximum buffering time?
-                                if buffer.len() > MAX_BUFFER_LENGTH
-                                    || time::Instant::now() - start > max_buffer_time
-                                {
-                                    // Flush the buffer
-                                    for v in &buffer {
-                                        output::print_entry(
-                                            &mut stdout,
-                                            v,
-                                            &config,
-                                            &wants_to_quit,
-                                        );
-                                    }
-                                    buffer.clear();
+    /// The size that size filters are applied to, or `None` if they do not apply to the entry.
+    /// They apply to files, and to directories if directory sizes are computed.
+    pub(crate) fn filtered_size(&self) -> Option<u64> {
+        match self.size.get() {
+            Some(&size) => Some(size),
+            None if self.path().is_file() => self.metadata().map(Metadata::len),
+            None => None,
+        }
+    }
 
-                                    // Start streaming
-                                    mode = ReceiverMode::Streaming;
-                                }
-                            }
-                            ReceiverMode::Streaming => {
-                                output::print_entry(&mut stdout, &value, &config, &wants_to_quit);
-                            }
-                        }
-                    }
-                    WorkerResult::Error(err) => {
-                        if show_filesystem_errors {
-                            print_error!("{}", err);
-                        }
-                    }
-                }
-            }
+    fn set_size(&self, size: u64) {
+        // The size is only computed once per entry, by the walker.
+        let _ = self.size.set(size);
+    }
 
-            // If we have finished fast enough (faster than max_buffer_time), we haven't streamed
-            // anything to the console, yet. In this case, sort the results and print them:
-            if !buffer.is_empty() {
-                buffer.sort();
-                for value in buffer {
-                    output::print_entry(&mut stdout, &value, &config, &wants_to_quit);
-                }
+    /// The MIME type of a regular file, detected from its first bytes. Other entries and files
+    /// that can not be read have none. The file is read at most once.
+    pub fn mime_type(&self) -> Option<&'static str> {
+        *self.mime_type.get_or_init(|| {
+            if self.metadata().is_some_and(Metadata::is_file) {
+                filter::detect_mime_type(self.path())
+            } else {
+                None
             }
+        })
+    }
 
-            ExitCode::Success
-        }
-    })
-}
+    /// The target of a symlink as it is stored in the link, i.e. without resolving it. This is
+    /// also available for symlinks that are followed. Other entries have none.
+    pub fn link_target(&self) -> Option<&Path> {
+        self.link_target
+            .get_or_init(|| {
+                let is_symlink = match &self.inner {
+                    DirEntryInner::Normal(e) => e.path_is_symlink(),
+                    DirEntryInner::BrokenSymlink { .. } => true,
+                };
+                if is_symlink {
+                    fs::read_link(self.path()).ok()
+                } else {
+                    None
+                }
+            })
+            .as_deref()
+    }
 
-pub enum DirEntry {
-    Normal(ignore::DirEntry),
-    BrokenSymlink(PathBuf),
-}
+    /// The extended attributes (names and values) that matched the filters on them, in the order
+    /// of the filters.
+    #[cfg(unix)]
+    pub fn xattrs(&self) -> &[Xattr] {
+        self.xattrs.get().map_or(&[], Vec::as_slice)
+    }
 
-impl DirEntry {
-    pub fn path(&self) -> &Path {
-        match self {
-            DirEntry::Normal(e) => e.path(),
-            DirEntry::BrokenSymlink(pathbuf) => pathbuf.as_path(),
-        }
+    /// The checksum of a regular file in lowercase hex digits, if checksums are computed and the
+    /// file could be read.
+    pub fn checksum(&self) -> Option<&str> {
+        self.checksum.get().map(String::as_str)
     }
 
-    pub fn file_type(&self) -> Option<FileType> {
-        match self {
-            DirEntry::Normal(e) => e.file_type(),
-            DirEntry::BrokenSymlink(pathbuf) => {
-                pathbuf.symlink_metadata().map(|m| m.file_type()).ok()
-            }
-        }
+    /// The index of the group of files with identical contents that the entry belongs to, if
+    /// duplicates are searched. Groups are numbered from `0`, in the order they are reported.
+    pub fn duplicate_group(&self) -> Option<usize> {
+        self.duplicate_group
     }
 
-    pub fn metadata(&self) -> Option<Metadata> {
-        match self {
-            DirEntry::Normal(e) => e.metadata().ok(),
-            DirEntry::BrokenSymlink(_) => None,
+    /// The depth at which the entry was found below its search root. Entries directly inside a
+    /// search root have a depth of `1`.
+    pub fn depth(&self) -> usize {
+        match &self.inner {
+            DirEntryInner::Normal(e) => e.depth(),
+            DirEntryInner::BrokenSymlink { depth, .. } => *depth,
         }
     }
 }
@@ -288,11 +323,13 @@ fn spawn_senders(
     config: &Arc<FdOptions>,
     wants_to_quit: &Arc<AtomicBool>,
     pattern: Arc<Regex>,
+    shared: &Arc<SharedState>,
     parallel_walker: ignore::WalkParallel,
-    tx: Sender<WorkerResult>,
+    tx: &Sender<WorkerResult>,
 ) {
     parallel_walker.run(|| {
         let config = Arc::clone(config);
+        let shared = Arc::clone(shared);
         let pattern = Arc::clone(&pattern);
         let tx_thread = tx.clone();
         let wants_to_quit = Arc::clone(wants_to_quit);
@@ -307,35 +344,27 @@ fn spawn_senders(
                     // Skip the root directory entry.
                     return ignore::WalkState::Continue;
                 }
-                Ok(e) => DirEntry::Normal(e),
-                Err(ignore::Error::WithPath {
-                    path,
-                    err: inner_err,
-                }) => match inner_err.as_ref() {
-                    ignore::Error::Io(io_error)
-                        if io_error.kind() == io::ErrorKind::NotFound
-                            && path
-                                .symlink_metadata()
-                                .ok()
-                                .map_or(false, |m| m.file_type().is_symlink()) =>
-                    {
-                        DirEntry::BrokenSymlink(path.to_owned())
+                Ok(e) => DirEntry::normal(e),
+                Err(err) => match broken_symlink_path(&err) {
+                    Some(path) => {
+                        // The walker does not report the depth of broken symlinks that it tried to
+                        // follow, so it is derived from the search root.
+                        let depth = err
+                            .depth()
+                            .unwrap_or_else(|| depth_below_roots(&shared.roots, path));
+                        DirEntry::broken_symlink(path.to_owned(), depth)
                     }
-                    _ => {
-                        tx_thread
-                            .send(WorkerResult::Error(ignore::Error::WithPath {
-                                path,
-                                err: inner_err,
-                            }))
-                            .unwrap();
-                        return ignore::WalkState::Continue;
+                    None => {
+                        return send_error(&tx_thread, err);
                     }
                 },
-                Err(err) => {
-                    tx_thread.send(WorkerResult::Error(err)).unwrap();
+            };
+
+            if let Some(min_depth) = config.min_depth {
+                if entry.depth() < min_depth {
                     return ignore::WalkState::Continue;
                 }
-            };
+            }
 
             // Check the name first, since it doesn't require metadata
             let entry_path = entry.path();
@@ -343,8 +372,14 @@ fn spawn_senders(
             let search_str: Cow<OsStr> = if config.search_full_path {
                 match fshelper::path_absolute_form(entry_path) {
                     Ok(path_abs_buf) => Cow::Owned(path_abs_buf.as_os_str().to_os_string()),
-                    Err(_) => {
-                        print_error_and_exit!("Unable to retrieve absolute path.");
+                    Err(err) => {
+                        return send_error(
+                            &tx_thread,
+                            ignore::Error::WithPath {
+                                path: entry_path.to_owned(),
+                                err: Box::new(ignore::Error::Io(err)),
+                            },
+                        );
                     }
                 }
             } else {
@@ -376,54 +411,45 @@ fn spawn_senders(
             let entry_metadata = entry.metadata();
 
             // Filter out unwanted file types.
-
             if let Some(ref file_types) = config.file_types {
-                if let Some(ref entry_type) = entry.file_type() {
-                    if (!file_types.files && entry_type.is_file())
-                        || (!file_types.directories && entry_type.is_dir())
-                        || (!file_types.symlinks && entry_type.is_symlink())
-                        || (file_types.executables_only
-                            && !entry_metadata
-                                .as_ref()
-                                .map(|m| fshelper::is_executable(&m))
-                                .unwrap_or(false))
-                        || (file_types.empty_only && !fshelper::is_empty(&entry))
-                        || !(entry_type.is_file() || entry_type.is_dir() || entry_type.is_symlink())
-                    {
-                        return ignore::WalkState::Continue;
-                    }
-                } else {
+                if !file_types.matches(&entry) {
                     return ignore::WalkState::Continue;
                 }
             }
 
-            // Filter out unwanted sizes if it is a file and we have been given size constraints.
-            if !config.size_constraints.is_empty() {
-                let mut matched = false;
-                if entry_path.is_file() {
-                    if let Some(ref metadata) = entry_metadata {
-                        let file_size = metadata.len();
-                        matched = config
-                            .size_constraints
-                            .iter()
-                            .all(|sc| sc.is_within(file_size));
-                    }
+            // Compute the total size of directories, or the allocated size of files, if requested.
+            let size = match shared.dir_sizes {
+                Some(ref dir_sizes) if entry.file_type().is_some_and(|ft| ft.is_dir()) => {
+                    Some(dir_sizes.total(entry_path))
+                }
+                _ if config.size_kind != SizeKind::Apparent && entry_path.is_file() => {
+                    entry_metadata.map(|metadata| config.size_kind.of(metadata))
                 }
+                _ => None,
+            };
+            if let Some(size) = size {
+                entry.set_size(size);
+            }
+
+            // Filter out unwanted sizes if it is a file (or a directory whose total size is
+            // computed) and we have been given size constraints.
+            if !config.size_constraints.is_empty() {
+                let matched = entry.filtered_size().is_some_and(|size| {
+                    config.size_constraints.iter().all(|sc| sc.is_within(size))
+                });
                 if !matched {
                     return ignore::WalkState::Continue;
                 }
             }
 
-            // Filter out unwanted modification times
+            // Filter out unwanted modification, access, change or creation times
             if !config.time_constraints.is_empty() {
                 let mut matched = false;
-                if let Some(ref metadata) = entry_metadata {
-                    if let Ok(modified) = metadata.modified() {
-                        matched = config
-                            .time_constraints
-                            .iter()
-                            .all(|tf| tf.applies_to(&modified));
-                    }
+                if let Some(metadata) = entry_metadata {
+                    matched = config
+                        .time_constraints
+                        .iter()
+                        .all(|tf| tf.matches(metadata));
                 }
                 if !matched {
                     return ignore::WalkState::Continue;
@@ -434,7 +460,7 @@ fn spawn_senders(
             {
                 if !config.owner_filters.is_empty() {
                     let mut matched = false;
-                    if let Some(ref metadata) = entry_metadata {
+                    if let Some(metadata) = entry_metadata {
                         let uid = fshelper::get_uid(metadata);
                         let gid = fshelper::get_gid(metadata);
                         matched = config
@@ -447,9 +473,21 @@ fn spawn_senders(
                     }
                 }
 
+                // The user and group databases are only queried once per id, see `fshelper`.
+                if config.no_user || config.no_group {
+                    let matched = entry_metadata.is_some_and(|metadata| {
+                        (!config.no_user || !fshelper::user_exists(fshelper::get_uid(metadata)))
+                            && (!config.no_group
+                                || !fshelper::group_exists(fshelper::get_gid(metadata)))
+                    });
+                    if !matched {
+                        return ignore::WalkState::Continue;
+                    }
+                }
+
                 if !config.permission_filters.is_empty() {
                     let mut matched = false;
-                    if let Some(ref metadata) = entry_metadata {
+                    if let Some(metadata) = entry_metadata {
                         let perm = fshelper::get_permission(metadata);
                         matched = config
                             .permission_filters
@@ -460,15 +498,217 @@ fn spawn_senders(
                         return ignore::WalkState::Continue;
                     }
                 }
+
+                if !config.links_filters.is_empty() || !config.inode_filters.is_empty() {
+                    let matched = entry_metadata.is_some_and(|metadata| {
+                        config.links_filters.iter().all(|f| f.matches(metadata))
+                            && config.inode_filters.iter().all(|f| f.matches(metadata))
+                    });
+                    if !matched {
+                        return ignore::WalkState::Continue;
+                    }
+                }
+            }
+
+            if let Some(ref expr) = config.filter_expression {
+                if !expr.matches(&entry, entry_metadata) {
+                    return ignore::WalkState::Continue;
+                }
+            }
+
+            if !config.link_target_filters.is_empty() {
+                let matched = entry.link_target().is_some_and(|target| {
+                    config.link_target_filters.iter().all(|f| f.matches(target))
+                });
+                if !matched {
+                    return ignore::WalkState::Continue;
+                }
+            }
+
+            // Access checks and extended attributes take extra system calls, so they are only
+            // done for entries that passed the metadata-based filters.
+            #[cfg(unix)]
+            {
+                if !config.access_filters.iter().all(|f| f.matches(entry_path)) {
+                    return ignore::WalkState::Continue;
+                }
+
+                if !config.xattr_filters.is_empty() {
+                    let mut xattrs = vec![];
+                    for filter in &config.xattr_filters {
+                        match filter.matching(entry_path, config.follow_links) {
+                            Some(matched) => {
+                                for xattr in matched {
+                                    if !xattrs.contains(&xattr) {
+                                        xattrs.push(xattr);
+                                    }
+                                }
+                            }
+                            None => return ignore::WalkState::Continue,
+                        }
+                    }
+                    let _ = entry.xattrs.set(xattrs);
+                }
+            }
+
+            // Only read the header of files once all metadata-based filters have passed.
+            if !config.mime_filters.is_empty() {
+                let matched = entry
+                    .mime_type()
+                    .is_some_and(|mime| config.mime_filters.iter().any(|f| f.matches(mime)));
+                if !matched {
+                    return ignore::WalkState::Continue;
+                }
+            }
+
+            // Read the contents last, since it is by far the most expensive check.
+            if !config.content_filters.is_empty() {
+                // Another thread may have reached the maximum number of results meanwhile.
+                if wants_to_quit.load(Ordering::Relaxed) {
+                    return ignore::WalkState::Quit;
+                }
+                if !entry_path.is_file()
+                    || !config.content_filters.iter().all(|f| f.matches(entry_path))
+                {
+                    return ignore::WalkState::Continue;
+                }
+            }
+
+            if config.checksum.is_some() || !config.checksum_filters.is_empty() {
+                if wants_to_quit.load(Ordering::Relaxed) {
+                    return ignore::WalkState::Quit;
+                }
+                if !checksums_match(&config, &entry, entry_metadata) {
+                    return ignore::WalkState::Continue;
+                }
+            }
+
+            // Report hard links to an inode that was already reported only once. Directories are
+            // exempt, since their subdirectories' '..' entries count as links as well.
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::MetadataExt;
+
+                if let (Some(seen_inodes), Some(metadata)) = (&shared.seen_inodes, entry_metadata) {
+                    if !metadata.is_dir()
+                        && metadata.nlink() > 1
+                        && !seen_inodes
+                            .lock()
+                            .unwrap()
+                            .insert((metadata.dev(), metadata.ino()))
+                    {
+                        return ignore::WalkState::Continue;
+                    }
+                }
+            }
+
+            // Duplicates are only reported once all files have been compared. Empty files are
+            // identical, but there is nothing to be gained from removing them.
+            if let Some(ref duplicates) = shared.duplicates {
+                if let Some(metadata) = entry_metadata.filter(|m| m.is_file() && m.len() > 0) {
+                    let size = metadata.len();
+                    duplicates.add(entry, size);
+                }
+                return ignore::WalkState::Continue;
             }
 
-            let send_result = tx_thread.send(WorkerResult::Entry(entry_path.to_owned()));
+            // Do not descend into a matched directory if it is to be pruned.
+            let prune = config.prune && entry.file_type().is_some_and(|ft| ft.is_dir());
 
-            if !send_result.is_ok() {
+            let send_result = tx_thread.send(WorkerResult::Entry(Box::new(entry)));
+
+            if send_result.is_err() {
                 return ignore::WalkState::Quit;
             }
 
-            ignore::WalkState::Continue
+            if prune {
+                ignore::WalkState::Skip
+            } else {
+                ignore::WalkState::Continue
+            }
         })
     });
 }
+
+/// Compute the checksum of a regular file if it is to be reported, and check it against the
+/// checksum filters. Entries that are not regular files or can not be read only pass if there
+/// are no checksum filters.
+fn checksums_match(config: &FdOptions, entry: &DirEntry, metadata: Option<&Metadata>) -> bool {
+    if !metadata.is_some_and(Metadata::is_file) {
+        return config.checksum_filters.is_empty();
+    }
+
+    if let Some(kind) = config.checksum {
+        if let Ok(digest) = kind.of_file(entry.path()) {
+            let _ = entry.checksum.set(digest);
+        }
+    }
+
+    config.checksum_filters.iter().all(|filter| {
+        if Some(filter.kind()) == config.checksum {
+            entry
+                .checksum()
+                .is_some_and(|digest| filter.matches(digest))
+        } else {
+            filter
+                .kind()
+                .of_file(entry.path())
+                .is_ok_and(|digest| filter.matches(&digest))
+        }
+    })
+}
+
+/// Send the groups of files with identical contents, if duplicates are searched, once the
+/// traversal has finished.
+fn send_duplicates(shared: &SharedState, tx: &Sender<WorkerResult>) {
+    let duplicates = match shared.duplicates {
+        Some(ref duplicates) => duplicates,
+        None => return,
+    };
+
+    for (group, entries) in duplicates.take_groups().into_iter().enumerate() {
+        for mut entry in entries {
+            entry.duplicate_group = Some(group);
+            if tx.send(WorkerResult::Entry(Box::new(entry))).is_err() {
+                return;
+            }
+        }
+    }
+}
+
+/// The path of a broken symlink, if this is the error for following it.
+fn broken_symlink_path(err: &ignore::Error) -> Option<&Path> {
+    match err {
+        ignore::Error::WithDepth { err, .. } => broken_symlink_path(err),
+        ignore::Error::WithPath { path, err } => match err.as_ref() {
+            ignore::Error::Io(io_error)
+                if io_error.kind() == io::ErrorKind::NotFound
+                    && path
+                        .symlink_metadata()
+                        .is_ok_and(|m| m.file_type().is_symlink()) =>
+            {
+                Some(path)
+            }
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// The depth of a path below the (innermost) search root that it starts with.
+fn depth_below_roots(roots: &[PathBuf], path: &Path) -> usize {
+    roots
+        .iter()
+        .filter_map(|root| path.strip_prefix(root).ok())
+        .map(|relative| relative.components().count())
+        .min()
+        .unwrap_or_else(|| path.components().count())
+}
+
+/// Report an error to the receiver. The traversal stops once nobody is listening anymore.
+fn send_error(tx: &Sender<WorkerResult>, err: ignore::Error) -> ignore::WalkState {
+    match tx.send(WorkerResult::Error(err)) {
+        Ok(_) => ignore::WalkState::Continue,
+        Err(_) => ignore::WalkState::Quit,
+    }
+}
@@ -6,7 +6,6 @@
 // notice may not be copied, modified, or distributed except
 // according to those terms.
 
-use std;
 use std::env;
 use std::fs;
 use std::io;
@@ -121,14 +120,14 @@ fn format_output_error(args: &[&str], expected: &str, actual: &str) -> String {
 }
 
 /// Normalize the output for comparison.
-fn normalize_output(s: &str, trim_start: bool, normalize_line: bool) -> String {
+fn normalize_output(s: &str, trim_start: bool, normalize_line: bool, sort_lines: bool) -> String {
     // Split into lines and normalize separators.
     let mut lines = s
         .replace('\0', "NULL\n")
         .lines()
         .map(|line| {
             let line = if trim_start { line.trim_start() } else { line };
-            let line = line.replace('/', &std::path::MAIN_SEPARATOR.to_string());
+            let line = line.replace('/', std::path::MAIN_SEPARATOR_STR);
             if normalize_line {
                 let mut words: Vec<_> = line.split_whitespace().collect();
                 words.sort();
@@ -138,7 +137,9 @@ fn normalize_output(s: &str, trim_start: bool, normalize_line: bool) -> String {
         })
         .collect::<Vec<_>>();
 
-    lines.sort();
+    if sort_lines {
+        lines.sort();
+    }
     lines.join("\n")
 }
 
@@ -197,6 +198,12 @@ impl TestEnv {
         self.assert_output_subdirectory(".", args, expected)
     }
 
+    /// Assert that calling *fd* with the specified arguments produces the expected output, in
+    /// exactly the given order.
+    pub fn assert_output_ordered(&self, args: &[&str], expected: &str) {
+        self.check_output(".", args, expected, false)
+    }
+
     /// Assert that calling *fd* in the specified path under the root working directory,
     /// and with the specified arguments produces the expected output.
     pub fn assert_output_subdirectory<P: AsRef<Path>>(
@@ -205,6 +212,16 @@ impl TestEnv {
         args: &[&str],
         expected: &str,
     ) {
+        self.check_output(path, args, expected, true)
+    }
+
+    /// Run *fd* in the specified path under the root working directory, assert that it exits
+    /// successfully, and return its output.
+    pub fn assert_success_and_get_output<P: AsRef<Path>>(
+        &self,
+        path: P,
+        args: &[&str],
+    ) -> process::Output {
         // Setup *fd* command.
         let mut cmd = process::Command::new(&self.fd_exe);
         cmd.current_dir(self.temp_dir.path().join(path));
@@ -215,20 +232,27 @@ impl TestEnv {
 
         // Check for exit status.
         if !output.status.success() {
-            panic!(format_exit_error(args, &output));
+            panic!("{}", format_exit_error(args, &output));
         }
 
+        output
+    }
+
+    fn check_output<P: AsRef<Path>>(&self, path: P, args: &[&str], expected: &str, sort: bool) {
+        let output = self.assert_success_and_get_output(path, args);
+
         // Normalize both expected and actual output.
-        let expected = normalize_output(expected, true, self.normalize_line);
+        let expected = normalize_output(expected, true, self.normalize_line, sort);
         let actual = normalize_output(
             &String::from_utf8_lossy(&output.stdout),
             false,
             self.normalize_line,
+            sort,
         );
 
         // Compare actual output to expected output.
         if expected != actual {
-            panic!(format_output_error(args, &expected, &actual));
+            panic!("{}", format_output_error(args, &expected, &actual));
         }
     }
 
@@ -256,7 +280,7 @@ impl TestEnv {
         // Compare actual output to expected output.
         let actual = String::from_utf8_lossy(&output.stderr);
         if !actual.starts_with(expected) {
-            panic!(format_output_error(args, &expected, &actual));
+            panic!("{}", format_output_error(args, expected, &actual));
         }
     }
 }
@@ -8,6 +8,8 @@
 
 //! Integration tests for the CLI interface of fd.
 
+#![allow(clippy::redundant_static_lifetimes)]
+
 mod testenv;
 
 use crate::testenv::TestEnv;
@@ -57,7 +59,7 @@ fn get_test_env_with_abs_path(dirs: &[&'static str], files: &[&'static str]) ->
 fn create_file_with_size<P: AsRef<Path>>(path: P, size_in_bytes: usize) {
     let content = "#".repeat(size_in_bytes);
     let mut f = fs::File::create::<P>(path).unwrap();
-    f.write(content.as_bytes()).unwrap();
+    f.write_all(content.as_bytes()).unwrap();
 }
 
 /// Simple tests
@@ -677,6 +679,73 @@ fn test_max_depth() {
     );
 }
 
+/// Minimum depth (--min-depth)
+#[test]
+fn test_min_depth() {
+    let te = TestEnv::new(DEFAULT_DIRS, DEFAULT_FILES);
+
+    te.assert_output(
+        &["--min-depth", "3"],
+        "one/two/c.foo
+        one/two/C.Foo2
+        one/two/three
+        one/two/three/d.foo
+        one/two/three/directory_foo",
+    );
+
+    te.assert_output(
+        &["--min-depth", "4"],
+        "one/two/three/d.foo
+        one/two/three/directory_foo",
+    );
+
+    te.assert_output(
+        &["--min-depth", "2", "--max-depth", "3"],
+        "one/b.foo
+        one/two
+        one/two/c.foo
+        one/two/C.Foo2
+        one/two/three",
+    );
+}
+
+/// Exact depth (--exact-depth)
+#[test]
+fn test_exact_depth() {
+    let te = TestEnv::new(DEFAULT_DIRS, DEFAULT_FILES);
+
+    te.assert_output(
+        &["--exact-depth", "3"],
+        "one/two/c.foo
+        one/two/C.Foo2
+        one/two/three",
+    );
+
+    te.assert_output(&["--exact-depth", "2", "foo"], "one/b.foo");
+}
+
+/// Depth selection also applies to broken symlinks that could not be followed
+#[test]
+fn test_min_depth_broken_symlink() {
+    let mut te = TestEnv::new(DEFAULT_DIRS, DEFAULT_FILES);
+    te.create_broken_symlink("one/two/broken_symlink")
+        .expect("Failed to create broken symlink.");
+
+    te.assert_output(
+        &["--follow", "--exact-depth", "3", "symlink"],
+        "one/two/broken_symlink",
+    );
+    te.assert_output(
+        &["--follow", "--exact-depth", "2", "broken"],
+        "symlink/broken_symlink",
+    );
+    te.assert_output(&["--follow", "--min-depth", "4", "broken"], "");
+    te.assert_output(
+        &["--follow", "--min-depth", "2", "broken", "one"],
+        "one/two/broken_symlink",
+    );
+}
+
 /// Absolute paths (--absolute-path)
 #[test]
 fn test_absolute_path() {
@@ -800,6 +869,7 @@ fn test_type_executable() {
 
     fs::OpenOptions::new()
         .create(true)
+        .truncate(true)
         .write(true)
         .mode(0o777)
         .open(te.test_root().join("executable-file.sh"))
@@ -1343,6 +1413,22 @@ fn test_size() {
 
     // Files with size equal 4 kibibytes.
     te.assert_output(&["", "--size", "+4ki", "--size", "-4ki"], "4_kibibytes.foo");
+    te.assert_output(&["", "--size", "=4ki"], "4_kibibytes.foo");
+    te.assert_output(&["", "--size", "=0.03k"], "30_bytes.foo");
+
+    // Files with size in a range.
+    te.assert_output(
+        &["", "--size", "11b..3k"],
+        "11_bytes.foo
+        30_bytes.foo
+        3_kilobytes.foo",
+    );
+    te.assert_output(&["", "--size", "2.9k..3.5ki"], "3_kilobytes.foo");
+
+    te.assert_error(
+        &["", "--size", "3k..2k"],
+        "[fd error]: '3k..2k' is not a valid size constraint",
+    );
 }
 
 #[cfg(test)]
@@ -1458,7 +1544,7 @@ fn test_base_directory() {
     let (te, abs_path) = get_test_env_with_abs_path(DEFAULT_DIRS, DEFAULT_FILES);
     let abs_base_dir = &format!("{abs_path}/one/two", abs_path = &abs_path);
     te.assert_output(
-        &["--base-directory", &abs_base_dir, "foo", &abs_path],
+        &["--base-directory", abs_base_dir, "foo", &abs_path],
         &format!(
             "{abs_path}/a.foo
             {abs_path}/one/b.foo
@@ -1470,3 +1556,1048 @@ fn test_base_directory() {
         ),
     );
 }
+
+#[test]
+fn test_sort() {
+    let te = TestEnv::new(&["dir", "dir/sub"], &[]);
+    create_file_with_size(te.test_root().join("b.txt"), 30);
+    create_file_with_size(te.test_root().join("a.rs"), 20);
+    create_file_with_size(te.test_root().join("dir/c.md"), 10);
+    create_file_with_size(te.test_root().join("dir/sub/a.txt"), 40);
+
+    te.assert_output_ordered(
+        &["--type", "f", "--sort", "path"],
+        "a.rs
+        b.txt
+        dir/c.md
+        dir/sub/a.txt",
+    );
+
+    te.assert_output_ordered(
+        &["--type", "f", "--sort", "name"],
+        "a.rs
+        dir/sub/a.txt
+        b.txt
+        dir/c.md",
+    );
+
+    te.assert_output_ordered(
+        &["--type", "f", "--sort", "size"],
+        "dir/c.md
+        a.rs
+        b.txt
+        dir/sub/a.txt",
+    );
+
+    te.assert_output_ordered(
+        &["--type", "f", "--sort", "depth"],
+        "a.rs
+        b.txt
+        dir/c.md
+        dir/sub/a.txt",
+    );
+
+    te.assert_output_ordered(
+        &["--type", "f", "--sort", "extension"],
+        "dir/c.md
+        a.rs
+        b.txt
+        dir/sub/a.txt",
+    );
+
+    te.assert_output_ordered(
+        &["--type", "f", "--sort", "size", "--reverse"],
+        "dir/sub/a.txt
+        b.txt
+        a.rs
+        dir/c.md",
+    );
+
+    te.assert_output_ordered(
+        &["--type", "f", "--reverse"],
+        "dir/sub/a.txt
+        dir/c.md
+        b.txt
+        a.rs",
+    );
+}
+
+/// Boolean filter expressions (--where)
+#[test]
+fn test_where() {
+    let te = TestEnv::new(&["dir.log"], &[]);
+
+    create_file_with_size(te.test_root().join("small.log"), 10);
+    create_file_with_size(te.test_root().join("big.log"), 2000);
+    create_file_with_size(te.test_root().join("small.txt"), 10);
+    create_file_with_size(te.test_root().join("big.txt"), 2000);
+
+    te.assert_output(
+        &["--where", "size>1k or ext=log"],
+        "big.log
+        big.txt
+        dir.log
+        small.log",
+    );
+
+    te.assert_output(
+        &["--where", "(size>1k || ext=log) and not type=d"],
+        "big.log
+        big.txt
+        small.log",
+    );
+
+    // 'and' binds tighter than 'or'; filters without an operator are combined with 'and'.
+    te.assert_output(
+        &["--where", "ext=txt or size<1k ext=log"],
+        "big.txt
+        small.log
+        small.txt",
+    );
+
+    // Size filters only hold for files, even when they are strict.
+    te.assert_output(&["--where", "size<1k ext=log"], "small.log");
+    te.assert_output(
+        &["--where", "ext!=txt"],
+        "big.log
+        dir.log
+        small.log
+        symlink",
+    );
+
+    // Multiple expressions and other filters have to hold as well.
+    te.assert_output(
+        &["--where", "ext=log", "--where", "type=f", "big"],
+        "big.log",
+    );
+    te.assert_output(&["--where", "ext=txt", "--size", "-1k"], "small.txt");
+
+    te.assert_error(
+        &["--where", "(ext=log"],
+        "[fd error]: '(ext=log' is not a valid filter expression: missing ')'.",
+    );
+    te.assert_error(
+        &["--where", "colour=red"],
+        "[fd error]: 'colour=red' is not a valid filter expression: unknown filter 'colour'.",
+    );
+}
+
+/// Pruning of matched directories (--prune)
+#[test]
+fn test_prune() {
+    let dirs = &["foo/a", "foo/bar/foo/b", "bar/foo", "bar/baz"];
+    let files = &["foo/foo.txt", "bar/foo.txt", "bar/baz/foo.txt"];
+    let te = TestEnv::new(dirs, files);
+
+    te.assert_output(
+        &["--prune", "foo"],
+        "foo
+        bar/foo
+        bar/foo.txt
+        bar/baz/foo.txt",
+    );
+
+    // Only directories that match the filters as well are pruned.
+    te.assert_output(
+        &["--prune", "--type", "f", "foo"],
+        "foo/foo.txt
+        bar/foo.txt
+        bar/baz/foo.txt",
+    );
+
+    te.assert_output(
+        &["--prune", "^bar$"],
+        "bar
+        foo/bar",
+    );
+}
+
+/// Limiting the number of results (--max-results, -1)
+#[test]
+fn test_max_results() {
+    let te = TestEnv::new(DEFAULT_DIRS, DEFAULT_FILES);
+
+    te.assert_output(&["--max-results=0", "c.foo"], "");
+    te.assert_output(&["--max-results=1", "b.foo"], "one/b.foo");
+    te.assert_output(
+        &["--max-results=2", "c.foo"],
+        "one/two/c.foo
+        one/two/C.Foo2",
+    );
+
+    // With --sort, the first results in sorted order are shown.
+    te.assert_output_ordered(
+        &["--sort", "path", "--max-results", "2", "foo"],
+        "a.foo
+        one/b.foo",
+    );
+
+    let assert_just_one_result_with_option = |option| {
+        let output = te.assert_success_and_get_output(".", &[option, "foo", "--type", "f"]);
+        let stdout = String::from_utf8_lossy(&output.stdout)
+            .trim()
+            .replace(&std::path::MAIN_SEPARATOR.to_string(), "/");
+        assert!(stdout.ends_with(".foo") && !stdout.contains('\n'));
+    };
+    assert_just_one_result_with_option("--max-results=1");
+    assert_just_one_result_with_option("-1");
+
+    // In exec mode, commands are only run for the limited number of entries.
+    let output = te.assert_success_and_get_output(
+        ".",
+        &[
+            "--max-results=2",
+            "--type",
+            "f",
+            "foo",
+            "--exec",
+            "echo",
+            "found",
+        ],
+    );
+    assert_eq!(String::from_utf8_lossy(&output.stdout).lines().count(), 2);
+}
+
+/// JSON lines output (--json)
+#[test]
+fn test_json() {
+    let te = TestEnv::new(&["one"], &[]);
+    create_file_with_size(te.test_root().join("one/a\"b.foo"), 11);
+
+    let output = te.assert_success_and_get_output(".", &["--json", "foo"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let lines: Vec<_> = stdout.lines().collect();
+    assert_eq!(lines.len(), 1);
+
+    let line = lines[0];
+    assert!(line.starts_with(r#"{"path":"one/a\"b.foo","type":"file","size":11,"#));
+    assert!(line.ends_with(r#","depth":2}"#));
+    assert!(line.contains(r#","mtime":""#));
+    #[cfg(unix)]
+    assert!(line.contains(r#","mode":"0"#));
+
+    let output = te.assert_success_and_get_output(".", &["--json", "--type", "d"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.starts_with(r#"{"path":"one","type":"directory","#));
+}
+
+/// JSON output of paths that are not valid UTF-8
+#[cfg(all(unix, not(target_os = "macos")))]
+#[test]
+fn test_json_invalid_utf8() {
+    use std::ffi::OsStr;
+    use std::os::unix::ffi::OsStrExt;
+
+    let te = TestEnv::new(&[], &[]);
+    fs::File::create(
+        te.test_root()
+            .join(OsStr::from_bytes(b"test\xFFinvalid.txt")),
+    )
+    .unwrap();
+
+    let output = te.assert_success_and_get_output(".", &["--json", "invalid"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.starts_with(r#"{"path_base64":"dGVzdP9pbnZhbGlkLnR4dA==","type":"file","#));
+}
+
+/// User-defined output format (--format)
+#[test]
+fn test_format() {
+    let te = TestEnv::new(&["one/two"], &[]);
+    create_file_with_size(te.test_root().join("one/two/c.foo"), 7);
+    create_file_with_size(te.test_root().join("one/b.foo"), 1234);
+
+    te.assert_output(
+        &["--format", r"{path}\t{size}\t{depth}", "foo"],
+        "one/b.foo\t1234\t2
+        one/two/c.foo\t7\t3",
+    );
+
+    te.assert_output(
+        &["--format", "{//} {/} {/.} {.} {{{type}}}", "c.foo"],
+        "one/two c.foo c one/two/c {file}",
+    );
+
+    te.assert_output(
+        &["--format", "{/}: {type}", "--type", "d", "two"],
+        "two: directory",
+    );
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+
+        let path = te.test_root().join("one/b.foo");
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o4750)).unwrap();
+        te.assert_output(&["--format", "{mode} {perm}", "b.foo"], "4750 -rwsr-x---");
+    }
+
+    te.assert_error(
+        &["--format", "{colour}"],
+        "[fd error]: '{colour}' is not a valid format string: unknown placeholder '{colour}'.",
+    );
+}
+
+/// Long listing (--list-details)
+#[test]
+fn test_list_details() {
+    let te = TestEnv::new(&["one"], &[]);
+    create_file_with_size(te.test_root().join("one/b.foo"), 10);
+    create_file_with_size(te.test_root().join("a.foo"), 3000);
+
+    let output = te.assert_success_and_get_output(".", &["--list-details", "foo"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let lines: Vec<_> = stdout.lines().collect();
+    assert_eq!(lines.len(), 2);
+
+    // Sorted by path, with the columns aligned.
+    assert!(lines[0].starts_with("-rw"));
+    assert!(lines[0].ends_with(" a.foo"));
+    assert!(lines[1].ends_with(" one/b.foo"));
+    assert_eq!(
+        lines[0].len() - "a.foo".len(),
+        lines[1].len() - "one/b.foo".len()
+    );
+    assert!(lines[0].contains(" 2.9K "));
+    assert!(lines[1].contains("   10 "));
+
+    let output = te.assert_success_and_get_output(".", &["-l", "--sort", "size", "foo"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.lines().next().unwrap().ends_with(" one/b.foo"));
+}
+
+/// Content filters (--contains, --not-contains)
+#[test]
+fn test_contains() {
+    let te = TestEnv::new(&["src"], &[]);
+    let root = te.test_root();
+    fs::write(root.join("src/main.rs"), "fn main() {\n    run();\n}\n").unwrap();
+    fs::write(root.join("src/lib.rs"), "pub fn run() {}\n").unwrap();
+    fs::write(root.join("src/data.bin"), b"fn main\0\x01").unwrap();
+
+    te.assert_output(&["--contains", "fn main"], "src/main.rs");
+    te.assert_output(
+        &["--contains", "run\\("],
+        "src/main.rs
+        src/lib.rs",
+    );
+    te.assert_output(&["--contains", "run", "--contains", "^pub"], "src/lib.rs");
+    te.assert_output(&["--not-contains", "fn main"], "src/lib.rs");
+    te.assert_output(
+        &["--contains", "^\\}$", "--not-contains", "pub"],
+        "src/main.rs",
+    );
+    te.assert_output(
+        &["--contains", "fn", "--max-results=1", "lib"],
+        "src/lib.rs",
+    );
+
+    te.assert_error(
+        &["--contains", "fn ("],
+        "[fd error]: 'fn (' is not a valid regular expression",
+    );
+}
+
+/// Filters on the access, inode change and creation times
+#[test]
+fn test_other_timestamps() {
+    let te = TestEnv::new(&[], &["old_access", "new_access"]);
+    let now = SystemTime::now();
+    let old = filetime::FileTime::from_system_time(now - Duration::from_secs(10 * 24 * 60 * 60));
+    let new = filetime::FileTime::from_system_time(now);
+    filetime::set_file_atime(te.test_root().join("old_access"), old).unwrap();
+    filetime::set_file_atime(te.test_root().join("new_access"), new).unwrap();
+
+    te.assert_output(&["--accessed-before", "5d"], "old_access");
+    te.assert_output(&["--accessed-within", "5d", "access"], "new_access");
+    te.assert_output(&["--where", "accessed-before=5d"], "old_access");
+
+    // Setting the access time changes the inode, so both were changed just now.
+    te.assert_output(
+        &["--inode-changed-within", "1h", "access"],
+        "old_access
+        new_access",
+    );
+    te.assert_output(&["--inode-changed-before", "1h"], "");
+
+    te.assert_error(
+        &["--created-within", "yesterday-ish"],
+        "[fd error]: 'yesterday-ish' is not a valid date or duration",
+    );
+}
+
+/// Calendar dates, time zones and intervals in time filters
+#[test]
+fn test_changed_between() {
+    let te = TestEnv::new(&[], &["dec2023", "jan2024", "feb2024"]);
+    change_file_modified(te.test_root().join("dec2023"), "2023-12-15T12:00:00Z");
+    change_file_modified(te.test_root().join("jan2024"), "2024-01-15T12:00:00Z");
+    change_file_modified(te.test_root().join("feb2024"), "2024-02-15T12:00:00Z");
+
+    te.assert_output(&["--changed-between", "2024-01..2024-01"], "jan2024");
+    te.assert_output(
+        &["--changed-between", "2024-01..2024-02"],
+        "jan2024
+        feb2024",
+    );
+    te.assert_output(
+        &[
+            "--changed-between",
+            "2024-01-15 11:00Z..2024-02-15 13:00+01:00",
+        ],
+        "jan2024
+        feb2024",
+    );
+    te.assert_output(&["--where", "changed-between=2023..2024-01-01"], "dec2023");
+    te.assert_output(
+        &["--changed-before", "2024-01-15 12:00:00 UTC", "20"],
+        "dec2023
+        jan2024",
+    );
+    te.assert_output(&["--changed-before", "yesterday", "feb"], "feb2024");
+
+    te.assert_error(
+        &["--changed-between", "2024-02..2024-01"],
+        "[fd error]: '2024-02..2024-01' is not a valid time interval",
+    );
+}
+
+/// Total directory sizes (--dir-size)
+#[test]
+fn test_dir_size() {
+    let te = TestEnv::new(&["big/sub", "small", "big/.hidden"], &[]);
+    create_file_with_size(te.test_root().join("big/sub/a"), 100_000);
+    create_file_with_size(te.test_root().join("big/.hidden/b"), 100_000);
+    create_file_with_size(te.test_root().join("small/c"), 10);
+
+    // Without --dir-size, size constraints only apply to files.
+    te.assert_output(&["--size", "+150k"], "");
+    te.assert_output(&["--dir-size", "--size", "+150k"], "big");
+    te.assert_output(
+        &["--dir-size", "--size", "+50k"],
+        "big
+        big/sub
+        big/sub/a",
+    );
+    te.assert_output(
+        &["--dir-size", "--size", "+50k", "--type", "f"],
+        "big/sub/a",
+    );
+    te.assert_output(&["--where", "size>150k"], "");
+
+    te.assert_output_ordered(
+        &["--dir-size", "--type", "d", "--sort", "size", "--reverse"],
+        "big
+        big/sub
+        small",
+    );
+
+    let output = te.assert_success_and_get_output(".", &["--dir-size", "--json", "^big$"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let size: u64 = stdout
+        .split("\"size\":")
+        .nth(1)
+        .and_then(|rest| rest.split(',').next())
+        .and_then(|size| size.parse().ok())
+        .unwrap();
+    assert!(
+        (200_000..250_000).contains(&size),
+        "unexpected size {}",
+        size
+    );
+}
+
+/// Allocated instead of apparent sizes (--allocated-size)
+#[cfg(unix)]
+#[test]
+fn test_allocated_size() {
+    let te = TestEnv::new(&[], &["dense"]);
+    create_file_with_size(te.test_root().join("dense"), 100_000);
+    let sparse = fs::File::create(te.test_root().join("sparse")).unwrap();
+    sparse.set_len(10_000_000).unwrap();
+
+    te.assert_output(
+        &["--size", "+50k"],
+        "dense
+        sparse",
+    );
+    te.assert_output(&["--allocated-size", "--size", "+50k"], "dense");
+    te.assert_output(&["--allocated-size", "--size", "-50k", "sparse"], "sparse");
+}
+
+/// Hard link and inode filters (--links, --inum, --samefile, --unique-inode)
+#[cfg(unix)]
+#[test]
+fn test_hard_links() {
+    use std::os::unix::fs::MetadataExt;
+
+    let te = TestEnv::new(&["sub"], &["single", "original"]);
+    let root = te.test_root();
+    fs::hard_link(root.join("original"), root.join("sub/link")).unwrap();
+
+    te.assert_output(
+        &["--links", "+2", "--type", "f"],
+        "original
+        sub/link",
+    );
+    te.assert_output(&["--links", "1", "--type", "f"], "single");
+    te.assert_output(&["--links", "-1", "--type", "f"], "single");
+
+    te.assert_output(
+        &["--samefile", &root.join("original").to_string_lossy()],
+        "original
+        sub/link",
+    );
+
+    let inode = fs::metadata(root.join("single")).unwrap().ino().to_string();
+    te.assert_output(&["--inum", &inode], "single");
+
+    let output = te.assert_success_and_get_output(".", &["--unique-inode", "--type", "f"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let mut lines: Vec<_> = stdout.lines().collect();
+    lines.sort_unstable();
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[1], "single");
+    assert!(lines[0] == "original" || lines[0] == "sub/link");
+
+    te.assert_error(
+        &["--links", "two"],
+        "[fd error]: 'two' is not a valid number of links",
+    );
+    te.assert_error(
+        &["--samefile", "does-not-exist"],
+        "[fd error]: Could not access 'does-not-exist'",
+    );
+}
+
+/// Comparisons with the timestamps of a reference file (--newer, --older)
+#[test]
+fn test_newer_older() {
+    let te = TestEnv::new(&[], &["stamp", "old.o", "same.o", "new.o"]);
+    change_file_modified(te.test_root().join("stamp"), "2024-01-15T12:00:00Z");
+    change_file_modified(te.test_root().join("old.o"), "2024-01-15T11:59:59Z");
+    change_file_modified(te.test_root().join("same.o"), "2024-01-15T12:00:00Z");
+    change_file_modified(te.test_root().join("new.o"), "2024-01-15T12:00:01Z");
+
+    te.assert_output(&["--newer", "stamp", "\\.o$"], "new.o");
+    te.assert_output(&["--older", "stamp", "\\.o$"], "old.o");
+    te.assert_output(
+        &["--where", "ext=o", "--newer", "old.o", "--older", "new.o"],
+        "same.o",
+    );
+
+    // Compare access times instead; the modification times are left alone.
+    let now = SystemTime::now();
+    let atime = |secs| filetime::FileTime::from_system_time(now - Duration::from_secs(secs));
+    filetime::set_file_atime(te.test_root().join("stamp"), atime(60)).unwrap();
+    filetime::set_file_atime(te.test_root().join("old.o"), atime(0)).unwrap();
+    filetime::set_file_atime(te.test_root().join("same.o"), atime(120)).unwrap();
+    filetime::set_file_atime(te.test_root().join("new.o"), atime(120)).unwrap();
+    te.assert_output(
+        &["--compare-time", "atime", "--newer", "stamp", "\\.o$"],
+        "old.o",
+    );
+
+    te.assert_error(
+        &["--newer", "missing"],
+        "[fd error]: Could not access 'missing':",
+    );
+}
+
+/// Groups of files with identical contents (--duplicates)
+#[test]
+fn test_duplicates() {
+    let te = TestEnv::new(&["sub"], &[]);
+    for (name, contents) in &[
+        ("a.jpg", "picture"),
+        ("sub/b.jpg", "picture"),
+        ("c.txt", "picture"),
+        ("d.jpg", "drawing"),
+        ("e.txt", "notes"),
+        ("sub/f.txt", "notes"),
+    ] {
+        fs::write(te.test_root().join(name), contents).unwrap();
+    }
+
+    te.assert_output_ordered(
+        &["--duplicates"],
+        "3 identical files of 7 bytes (14 bytes wasted):
+        a.jpg
+        c.txt
+        sub/b.jpg
+
+        2 identical files of 5 bytes (5 bytes wasted):
+        e.txt
+        sub/f.txt",
+    );
+    te.assert_output_ordered(
+        &["--duplicates", "-e", "jpg"],
+        "2 identical files of 7 bytes (7 bytes wasted):
+        a.jpg
+        sub/b.jpg",
+    );
+    te.assert_output_ordered(
+        &["--duplicates", "--max-results", "1", "txt"],
+        "2 identical files of 5 bytes (5 bytes wasted):
+        e.txt
+        sub/f.txt",
+    );
+
+    let output = te.assert_success_and_get_output(".", &["--duplicates", "--json", "jpg"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let lines: Vec<_> = stdout.lines().collect();
+    assert_eq!(lines.len(), 1);
+    assert!(lines[0].starts_with(r#"{"size":7,"wasted":7,"files":[{"path":"a.jpg","#));
+    assert!(lines[0].contains(r#"},{"path":"sub/b.jpg","#));
+    assert!(lines[0].ends_with(r#","depth":2}]}"#));
+}
+
+/// Files found through several paths are not duplicates of themselves (--duplicates)
+#[test]
+fn test_duplicates_of_the_same_file() {
+    let te = TestEnv::new(&["sub"], &[]);
+    fs::write(te.test_root().join("sub/a.txt"), "notes").unwrap();
+    fs::write(te.test_root().join("sub/b.txt"), "notes").unwrap();
+    fs::write(te.test_root().join("sub/c.jpg"), "picture").unwrap();
+
+    te.assert_output_ordered(
+        &["--duplicates", ".", ".", "sub"],
+        "2 identical files of 5 bytes (5 bytes wasted):
+        sub/a.txt
+        sub/b.txt",
+    );
+
+    #[cfg(unix)]
+    {
+        fs::hard_link(
+            te.test_root().join("sub/c.jpg"),
+            te.test_root().join("d.jpg"),
+        )
+        .unwrap();
+        te.assert_output_ordered(&["--duplicates", "jpg"], "");
+
+        fs::hard_link(
+            te.test_root().join("sub/a.txt"),
+            te.test_root().join("e.txt"),
+        )
+        .unwrap();
+        te.assert_output_ordered(
+            &["--duplicates", "txt"],
+            "2 identical files of 5 bytes (5 bytes wasted):
+            e.txt
+            sub/b.txt",
+        );
+    }
+}
+
+/// Checksums of files (--checksum, --checksum-matches)
+#[test]
+fn test_checksum() {
+    let te = TestEnv::new(&["dist"], &[]);
+    fs::write(te.test_root().join("dist/a.tar"), "abc").unwrap();
+    fs::write(te.test_root().join("dist/b.tar"), "").unwrap();
+
+    te.assert_output(
+        &["--checksum", "sha256", "tar"],
+        "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad  dist/a.tar
+        e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855  dist/b.tar",
+    );
+    te.assert_output(
+        &["--checksum", "md5", "dist"],
+        "-                                 dist",
+    );
+    te.assert_output(
+        &[
+            "--checksum",
+            "blake3",
+            "--format",
+            "{checksum} {/}",
+            "a.tar",
+        ],
+        "6437b3ac38465133ffb63b75273a8db548c558465d79db03fd359c6cd5bd9d85 a.tar",
+    );
+
+    te.assert_output(
+        &["--checksum-matches", "900150983CD24FB0D6963F7D28E17F72"],
+        "dist/a.tar",
+    );
+    te.assert_output(
+        &[
+            "--checksum-matches",
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+        ],
+        "dist/b.tar",
+    );
+    te.assert_output(
+        &[
+            "--checksum",
+            "blake3",
+            "--checksum-matches",
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+        ],
+        "",
+    );
+
+    let output = te.assert_success_and_get_output(".", &["--checksum", "md5", "--json", "a.tar"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout
+        .trim_end()
+        .ends_with(r#","md5":"900150983cd24fb0d6963f7d28e17f72"}"#));
+
+    te.assert_error(
+        &["--checksum", "md5", "--checksum-matches", "abc"],
+        "[fd error]: 'abc' is not a valid md5 checksum",
+    );
+}
+
+/// Filter by the MIME type of files (--mime)
+#[test]
+fn test_mime() {
+    let te = TestEnv::new(&["bin"], &[]);
+    for (name, contents) in &[
+        ("bin/deploy", &b"#!/usr/bin/env bash\nset -e\n"[..]),
+        ("bin/tool.py", b"#!/usr/bin/python3\nprint()\n"),
+        ("photo.dat", b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR"),
+        ("cover.jpg", b"\xff\xd8\xff\xe0\0\x10JFIF\0"),
+        ("notes", b"nothing to see\n"),
+    ] {
+        fs::write(te.test_root().join(name), contents).unwrap();
+    }
+
+    te.assert_output(
+        &["--mime", "image/*"],
+        "photo.dat
+        cover.jpg",
+    );
+    te.assert_output(&["--mime", "text/x-shellscript"], "bin/deploy");
+    te.assert_output(
+        &["--mime", "TEXT/*", "--mime", "image/png"],
+        "bin/deploy
+        bin/tool.py
+        notes
+        photo.dat",
+    );
+    te.assert_output(&["--mime", "image/*", "-e", "jpg"], "cover.jpg");
+    te.assert_output(
+        &["--format", "{/} {mime}", "--type", "f", "o"],
+        "photo.dat image/png
+        cover.jpg image/jpeg
+        deploy text/x-shellscript
+        tool.py text/x-script.python
+        notes text/plain",
+    );
+
+    te.assert_error(
+        &["--mime", "image"],
+        "[fd error]: 'image' is not a valid MIME type pattern",
+    );
+}
+
+/// Filter by extended attributes (--xattr, --has-capabilities)
+#[cfg(unix)]
+#[test]
+fn test_xattr() {
+    let te = TestEnv::new(&[], &["plain", "labeled", "tagged"]);
+    let set = |name: &str, attribute: &str, value: &[u8]| {
+        xattr::set(te.test_root().join(name), attribute, value).is_ok()
+    };
+    if !set("labeled", "user.label", b"web_content_t\0") || !set("tagged", "user.tag", b"\xff") {
+        // The file system does not support user attributes.
+        return;
+    }
+
+    te.assert_output(
+        &["--xattr", "user.*"],
+        "labeled
+        tagged",
+    );
+    te.assert_output(&["--xattr", "user.label=web_*"], "labeled");
+    te.assert_output(&["--xattr", "user.label=db_*"], "");
+    te.assert_output(&["--xattr", "user.label", "--xattr", "user.tag"], "");
+    te.assert_output(&["--has-capabilities"], "");
+
+    let output = te.assert_success_and_get_output(".", &["--xattr", "user.*", "--json"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains(r#","xattrs":[{"name":"user.label","value":"web_content_t"}]"#));
+    assert!(stdout.contains(r#","xattrs":[{"name":"user.tag","value_base64":"/w=="}]"#));
+
+    te.assert_error(
+        &["--xattr", "=value"],
+        "[fd error]: '=value' is not a valid extended attribute filter",
+    );
+}
+
+/// Special file types (--type socket, pipe, block-device, char-device, other)
+#[cfg(all(unix, not(target_os = "redox")))]
+#[test]
+fn test_type_special() {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+    use std::os::unix::fs::symlink;
+    use std::os::unix::net::UnixListener;
+
+    let te = TestEnv::new(&[], &["file"]);
+    let _listener = UnixListener::bind(te.test_root().join("socket")).unwrap();
+    let fifo = CString::new(te.test_root().join("fifo").as_os_str().as_bytes()).unwrap();
+    assert_eq!(unsafe { libc::mkfifo(fifo.as_ptr(), 0o644) }, 0);
+    symlink("/dev/null", te.test_root().join("null")).unwrap();
+
+    te.assert_output(&["--type", "socket"], "socket");
+    te.assert_output(&["--type", "p"], "fifo");
+    te.assert_output(
+        &["--type", "other"],
+        "fifo
+        socket",
+    );
+    te.assert_output(
+        &["--type", "s", "--type", "pipe", "--type", "f"],
+        "fifo
+        file
+        socket",
+    );
+    te.assert_output(&["--type", "char-device"], "");
+    te.assert_output(&["--follow", "--type", "char-device"], "null");
+    te.assert_output(&["--type", "block-device"], "");
+    te.assert_output(&["--where", "type=p,s"], "fifo\nsocket");
+}
+
+/// Broken symlinks and symlink targets (--type broken-symlink, --link-target, --show-link-target)
+#[cfg(unix)]
+#[test]
+fn test_broken_symlinks_and_targets() {
+    use std::os::unix::fs::symlink;
+
+    let te = TestEnv::new(&["lib", "one/two"], &["lib/libfoo.so.1"]);
+    let root = te.test_root();
+    symlink("libfoo.so.1", root.join("lib/libfoo.so")).unwrap();
+    symlink("../old-package/bin/tool", root.join("tool")).unwrap();
+    symlink("../old-package/share", root.join("share")).unwrap();
+
+    te.assert_output(
+        &["--type", "broken-symlink"],
+        "share
+        tool",
+    );
+    te.assert_output(
+        &["--follow", "--type", "broken-symlink"],
+        "share
+        tool",
+    );
+    te.assert_output(
+        &["--type", "l"],
+        "lib/libfoo.so
+        share
+        symlink
+        tool",
+    );
+    te.assert_output(&["--link-target", "^libfoo"], "lib/libfoo.so");
+    te.assert_output(
+        &["--link-target", "old-package", "--link-target", "/bin/"],
+        "tool",
+    );
+    te.assert_output(&["--follow", "--link-target", "^libfoo"], "lib/libfoo.so");
+    te.assert_output(
+        &["--link-target", "."],
+        "lib/libfoo.so\nshare\nsymlink\ntool",
+    );
+
+    te.assert_output(
+        &["--show-link-target", "libfoo"],
+        "lib/libfoo.so -> libfoo.so.1
+        lib/libfoo.so.1",
+    );
+    te.assert_output(
+        &["--format", "{}:{target}", "tool"],
+        "tool:../old-package/bin/tool",
+    );
+
+    let output = te.assert_success_and_get_output(".", &["-l", "--type", "broken-symlink"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains(" share -> ../old-package/share\n"));
+
+    let output = te.assert_success_and_get_output(".", &["--json", "libfoo"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains(r#","target":"libfoo.so.1""#));
+    assert_eq!(stdout.matches(r#""target""#).count(), 1);
+
+    te.assert_error(
+        &["--link-target", "("],
+        "[fd error]: '(' is not a valid regular expression",
+    );
+}
+
+/// Files without a user or group account (--nouser, --nogroup)
+#[cfg(unix)]
+#[test]
+fn test_nouser_nogroup() {
+    use std::ffi::CString;
+    use std::io;
+    use std::os::unix::ffi::OsStrExt;
+    use std::path::Path;
+
+    // `std::os::unix::fs::{chown, lchown}` need Rust 1.73. An id of -1 leaves it unchanged.
+    fn chown(path: &Path, uid: u32, gid: u32, follow: bool) -> io::Result<()> {
+        let path = CString::new(path.as_os_str().as_bytes()).unwrap();
+        let ret = unsafe {
+            if follow {
+                libc::chown(path.as_ptr(), uid, gid)
+            } else {
+                libc::lchown(path.as_ptr(), uid, gid)
+            }
+        };
+        if ret == 0 {
+            Ok(())
+        } else {
+            Err(io::Error::last_os_error())
+        }
+    }
+
+    let te = TestEnv::new(&[], &["owned", "orphan", "orphan_group"]);
+    let root = te.test_root();
+    // Ids that no account on a test system has.
+    let missing = 3_999_999;
+    if chown(&root.join("orphan"), missing, missing, true).is_err() {
+        // Changing the owner requires privileges.
+        return;
+    }
+    chown(&root.join("orphan_group"), u32::MAX, missing, true).unwrap();
+    chown(&root.join("symlink"), missing, u32::MAX, false).unwrap();
+
+    te.assert_output(
+        &["--nouser"],
+        "orphan
+        symlink",
+    );
+    te.assert_output(
+        &["--nogroup"],
+        "orphan
+        orphan_group",
+    );
+    te.assert_output(&["--nouser", "--nogroup"], "orphan");
+    te.assert_output(&["--nouser", "--type", "f"], "orphan");
+
+    let output = te.assert_success_and_get_output(".", &["-l", "--nogroup"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains(" 3999999 3999999 "));
+}
+
+/// Access of the current or a given user (--readable, --writable, --executable-by-me)
+#[cfg(all(unix, not(target_os = "redox")))]
+#[test]
+fn test_access() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let te = TestEnv::new(&["dir"], &["script", "plain", "locked"]);
+    let root = te.test_root();
+    for (name, mode) in &[("script", 0o744), ("plain", 0o644), ("locked", 0o000)] {
+        fs::set_permissions(root.join(name), fs::Permissions::from_mode(*mode)).unwrap();
+    }
+
+    te.assert_output(&["--executable-by-me", "--type", "f"], "script");
+    te.assert_output(&["--type", "x"], "script");
+    te.assert_output(&["--executable-by-me", "--type", "d"], "dir");
+
+    // `root` may read and write anything.
+    let readable = if unsafe { libc::geteuid() } == 0 {
+        "locked\nplain\nscript"
+    } else {
+        "plain\nscript"
+    };
+    te.assert_output(&["--readable", "--type", "f"], readable);
+    te.assert_output(&["--writable", "--type", "f"], readable);
+
+    te.assert_output(
+        &["--writable", "--access-user", "0", "--type", "f"],
+        "locked
+        plain
+        script",
+    );
+    te.assert_output(
+        &["--readable", "--executable-by-me", "--access-user", "root"],
+        "dir
+        script",
+    );
+
+    te.assert_error(
+        &["--access-user", "root"],
+        "[fd error]: '--access-user' requires '--readable', '--writable' or '--executable-by-me'.",
+    );
+    te.assert_error(
+        &["--readable", "--access-user", "no-such-user-fd"],
+        "[fd error]: 'no-such-user-fd' is not a known user",
+    );
+}
+
+/// Entries with equal keys stay in path order with --reverse
+#[test]
+fn test_sort_reverse_equal_keys() {
+    let te = TestEnv::new(&[], &[]);
+    create_file_with_size(te.test_root().join("a.txt"), 10);
+    create_file_with_size(te.test_root().join("b.txt"), 10);
+    create_file_with_size(te.test_root().join("c.txt"), 20);
+    create_file_with_size(te.test_root().join("d.txt"), 10);
+
+    te.assert_output_ordered(
+        &["--type", "f", "--sort", "size", "--reverse"],
+        "c.txt
+        a.txt
+        b.txt
+        d.txt",
+    );
+    te.assert_output_ordered(
+        &["--type", "f", "--sort", "size"],
+        "a.txt
+        b.txt
+        d.txt
+        c.txt",
+    );
+}
+
+/// Invalid limits are rejected instead of being ignored
+#[test]
+fn test_invalid_limits() {
+    let te = TestEnv::new(DEFAULT_DIRS, DEFAULT_FILES);
+
+    te.assert_error(
+        &["--max-results", "foo"],
+        "[fd error]: 'foo' is not a valid value for '--max-results'",
+    );
+    te.assert_error(
+        &["--min-depth=-1"],
+        "[fd error]: '-1' is not a valid value for '--min-depth'",
+    );
+    te.assert_error(
+        &["--exact-depth", "two"],
+        "[fd error]: 'two' is not a valid value for '--exact-depth'",
+    );
+}
+
+/// Only symlinks to missing targets and symlink loops are broken (--type broken-symlink)
+#[cfg(unix)]
+#[test]
+fn test_broken_symlink_errors() {
+    use std::os::unix::fs::{symlink, PermissionsExt};
+
+    let te = TestEnv::new(&["locked", "one/two"], &["file", "locked/secret"]);
+    let root = te.test_root();
+    symlink("loop", root.join("loop")).unwrap();
+    symlink("file/missing", root.join("through_file")).unwrap();
+    symlink("locked/secret", root.join("hidden")).unwrap();
+
+    te.assert_output(
+        &["--type", "broken-symlink"],
+        "loop
+        through_file",
+    );
+
+    let locked = root.join("locked");
+    fs::set_permissions(&locked, fs::Permissions::from_mode(0o000)).unwrap();
+    // Without privileges, the target of `hidden` cannot be looked at, but it still exists.
+    let denied = fs::metadata(root.join("hidden")).is_err();
+    let output = te.assert_success_and_get_output(".", &["--type", "broken-symlink"]);
+    fs::set_permissions(&locked, fs::Permissions::from_mode(0o755)).unwrap();
+
+    if denied {
+        assert!(!String::from_utf8_lossy(&output.stdout).contains("hidden"));
+    }
+}